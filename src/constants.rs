@@ -0,0 +1,49 @@
+/// Constants which can be used in any calculation, these cant be assigned to
+pub(crate) const CONSTANTS: &[(&str, f64)] = &[
+    ("pi", std::f64::consts::PI),
+    ("e", std::f64::consts::E),
+    ("tau", std::f64::consts::TAU),
+    //The golden ratio
+    ("phi", 1.618_033_988_749_895),
+    ("inf", f64::INFINITY),
+    ("nan", f64::NAN),
+];
+
+/// Physical constants in SI units, these are behind a feature because they take away common variable names like ```c``` and ```G```
+#[cfg(feature = "physical-constants")]
+pub(crate) const PHYSICAL_CONSTANTS: &[(&str, f64)] = &[
+    //Speed of light in vacuum (m/s)
+    ("c", 299_792_458.),
+    //Standard gravity (m/s^2)
+    ("g0", 9.806_65),
+    //Newtonian constant of gravitation (m^3/(kg*s^2))
+    ("G", 6.674_30e-11),
+    //Planck constant (J*s)
+    ("planck", 6.626_070_15e-34),
+    //Boltzmann constant (J/K)
+    ("boltzmann", 1.380_649e-23),
+    //Avogadro constant (1/mol)
+    ("avogadro", 6.022_140_76e23),
+];
+
+/// Every constant, including the physical ones if they are enabled
+fn all_constants() -> impl Iterator<Item = &'static (&'static str, f64)> {
+    #[cfg(feature = "physical-constants")]
+    let physical_constants = PHYSICAL_CONSTANTS;
+    #[cfg(not(feature = "physical-constants"))]
+    let physical_constants: &[(&str, f64)] = &[];
+
+    CONSTANTS.iter().chain(physical_constants)
+}
+
+/// Look up a constant by its name
+pub(crate) fn constant(name: &str) -> Option<f64> {
+    all_constants()
+        .find(|(constant_name, _)| *constant_name == name)
+        .map(|(_, value)| *value)
+}
+
+/// The names of the constants, these are suggested for misspelled names
+pub(crate) fn constant_names() -> impl Iterator<Item = &'static str> {
+    all_constants().map(|(name, _)| *name)
+}
@@ -0,0 +1,141 @@
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{number::Literal, units::Unit};
+
+/// ISO 4217 codes of common currencies, these are recognized without an exchange rate so the error can say that the rate is missing
+const CURRENCY_CODES: &[&str] = &[
+    "AED", "ARS", "AUD", "BGN", "BRL", "CAD", "CHF", "CLP", "CNY", "COP", "CZK", "DKK", "EGP",
+    "EUR", "GBP", "HKD", "HUF", "IDR", "ILS", "INR", "ISK", "JPY", "KRW", "MXN", "MYR", "NGN",
+    "NOK", "NZD", "PHP", "PKR", "PLN", "RON", "RUB", "SAR", "SEK", "SGD", "THB", "TRY", "TWD",
+    "UAH", "USD", "VND", "ZAR",
+];
+
+/// Why an exchange rate table couldnt be loaded
+#[derive(Debug, Error)]
+pub enum ExchangeRateError {
+    #[error("The rate table couldnt be read: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The CSV rate table is invalid: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("The JSON rate table is invalid: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Row {0} of the CSV rate table needs a currency and a rate")]
+    MissingColumns(u64),
+
+    #[error("The JSON rate table needs a `rates` object")]
+    MissingRates,
+
+    #[error("`{rate}` isnt a valid exchange rate for `{currency}`")]
+    InvalidRate { currency: String, rate: String },
+
+    #[error("Rate tables have to be .csv or .json files")]
+    UnknownFormat,
+}
+
+/// Exchange rates relative to a base currency, a rate of 0.92 for EUR means one unit of the base currency is worth 0.92 EUR
+/// The base currency itself has a rate of 1, the rates are only loaded from local files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExchangeRates {
+    rates: HashMap<String, Literal>,
+}
+
+impl ExchangeRates {
+    /// Read a table with the columns ```currency,rate```, every row after the header is a currency like ```EUR,0.92```
+    pub fn from_csv(reader: impl Read) -> Result<Self, ExchangeRateError> {
+        let mut exchange_rates = ExchangeRates::default();
+
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        for record in reader.records() {
+            let record = record?;
+
+            //Every row has as many fields as the header, but the header can have too few
+            let (Some(currency), Some(rate)) = (record.get(0), record.get(1)) else {
+                return Err(ExchangeRateError::MissingColumns(
+                    record.position().map_or(0, csv::Position::line),
+                ));
+            };
+
+            exchange_rates.insert(currency, rate)?;
+        }
+
+        Ok(exchange_rates)
+    }
+
+    /// Read a table like ```{"base": "USD", "rates": {"EUR": 0.92, "GBP": 0.79}}```, the base currency is optional
+    pub fn from_json(reader: impl Read) -> Result<Self, ExchangeRateError> {
+        let table: Value = serde_json::from_reader(reader)?;
+
+        let mut exchange_rates = ExchangeRates::default();
+
+        if let Some(base) = table.get("base").and_then(Value::as_str) {
+            exchange_rates.insert(base, "1")?;
+        }
+
+        let Some(rates) = table.get("rates").and_then(Value::as_object) else {
+            return Err(ExchangeRateError::MissingRates);
+        };
+
+        for (currency, rate) in rates {
+            //The rates can be numbers or strings
+            let rate = match rate {
+                Value::String(rate) => rate.clone(),
+                rate => rate.to_string(),
+            };
+
+            exchange_rates.insert(currency, &rate)?;
+        }
+
+        Ok(exchange_rates)
+    }
+
+    /// Read a CSV or JSON table from a file, the format is chosen by the extension
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ExchangeRateError> {
+        let path = path.as_ref();
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => ExchangeRates::from_csv(File::open(path)?),
+            Some("json") => ExchangeRates::from_json(File::open(path)?),
+            _ => Err(ExchangeRateError::UnknownFormat),
+        }
+    }
+
+    fn insert(&mut self, currency: &str, rate: &str) -> Result<(), ExchangeRateError> {
+        match Literal::parse(rate) {
+            Some(literal) if literal.float > 0. && literal.float.is_finite() => {
+                self.rates.insert(currency.to_string(), literal);
+
+                Ok(())
+            }
+            _ => Err(ExchangeRateError::InvalidRate {
+                currency: currency.to_string(),
+                rate: rate.to_string(),
+            }),
+        }
+    }
+
+    /// The unit of a currency, None if there is no rate for it
+    pub(crate) fn unit(&self, currency: &str) -> Option<Unit> {
+        self.rates
+            .get(currency)
+            .map(|rate| Unit::currency(currency, rate))
+    }
+
+    /// The currencies which have a rate
+    pub(crate) fn currencies(&self) -> impl Iterator<Item = &str> {
+        self.rates.keys().map(String::as_str)
+    }
+
+    /// Whether ```name``` is a currency, even if there is no rate for it
+    pub(crate) fn is_currency(&self, name: &str) -> bool {
+        self.rates.contains_key(name) || CURRENCY_CODES.contains(&name)
+    }
+}
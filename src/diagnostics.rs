@@ -0,0 +1,513 @@
+use std::{fmt::Write, ops::Range};
+
+use crate::{
+    constants::constant_names,
+    functions::BUILTIN_FUNCTIONS,
+    parser::{self, function_header},
+    syntax::{RecoveredParse, SyntaxNode},
+    tokenizer::{tokenize_with_recovery, Span, Token, TokenKind},
+    units::unit_names,
+    Calculator, CalculatorError, CalculatorErrorType, PercentMode, Warning,
+};
+
+/// A problem with the input, with everything needed to show it like a compiler would
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    /// The parts of the input the problem is about, the first one is the primary label
+    pub labels: Vec<Label>,
+    /// Explanations which dont belong to a part of the input
+    pub notes: Vec<String>,
+    pub suggestion: Option<Suggestion>,
+}
+
+/// A part of the input which is marked in a diagnostic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    /// The byte range of the input, this is empty if something is missing at that point
+    pub span: Range<usize>,
+    pub message: String,
+    /// The primary label marks the cause of the problem, secondary labels add context
+    pub primary: bool,
+}
+
+/// A replacement which most likely fixes the problem, like ```sqrt``` for ```sqr```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// The byte range of the input which should be replaced
+    pub span: Range<usize>,
+    pub replacement: String,
+}
+
+impl Label {
+    fn new(span: Span, message: impl Into<String>, primary: bool) -> Self {
+        Self {
+            span: span.range(),
+            message: message.into(),
+            primary,
+        }
+    }
+}
+
+impl Diagnostic {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestion: None,
+        }
+    }
+
+    fn with_primary_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label::new(span, message, true));
+        self
+    }
+
+    fn with_secondary_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label::new(span, message, false));
+        self
+    }
+
+    fn with_note(mut self, note: Option<&str>) -> Self {
+        self.notes.extend(note.map(str::to_string));
+        self
+    }
+
+    /// The part of the input the problem is about, two diagnostics with the same one are about the same problem
+    fn primary_span(&self) -> Option<&Range<usize>> {
+        self.labels.first().map(|label| &label.span)
+    }
+
+    /// Where the diagnostic is in the input, diagnostics are sorted by this
+    fn start(&self) -> usize {
+        self.labels.first().map_or(0, |label| label.span.start)
+    }
+
+    /// Render the diagnostic like a compiler error, ```input``` is the input the spans point into
+    /// The primary label is underlined with ```^```, the secondary labels with ```-```:
+    /// ```text
+    /// error: There is no variable named `sqr`
+    ///   |
+    /// 1 | sqr(4)
+    ///   | ^^^ unknown variable
+    ///   |
+    ///   = help: did you mean `sqrt`?
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        let mut rendered = format!("error: {}\n", self.message);
+
+        //The lines of the input which have labels, with the byte index they start at
+        let mut lines: Vec<(usize, usize, &str)> = Vec::new();
+
+        for label in &self.labels {
+            let line_start = input[..label.span.start]
+                .rfind('\n')
+                .map_or(0, |index| index + 1);
+            let line_number = input[..line_start].matches('\n').count() + 1;
+
+            if !lines.iter().any(|(number, ..)| *number == line_number) {
+                let line = input[line_start..].lines().next().unwrap_or("");
+
+                lines.push((line_number, line_start, line));
+            }
+        }
+
+        lines.sort_unstable();
+
+        let gutter_width = lines
+            .last()
+            .map_or(1, |(number, ..)| number.to_string().len());
+        let gutter = " ".repeat(gutter_width);
+
+        //Writing to a String cant fail
+        let _ = writeln!(rendered, "{gutter} |");
+
+        for (line_number, line_start, line) in lines {
+            let _ = writeln!(rendered, "{line_number:gutter_width$} | {line}");
+
+            let mut labels: Vec<&Label> = self
+                .labels
+                .iter()
+                .filter(|label| (line_start..=line_start + line.len()).contains(&label.span.start))
+                .collect();
+
+            labels.sort_by_key(|label| label.span.start);
+
+            for label in labels {
+                //The marks are placed by characters, not bytes
+                let offset = input[line_start..label.span.start].chars().count();
+                let end = label
+                    .span
+                    .end
+                    .min(line_start + line.len())
+                    .max(label.span.start);
+                let width = input[label.span.start..end].chars().count().max(1);
+
+                let mark = if label.primary { "^" } else { "-" };
+
+                let _ = writeln!(
+                    rendered,
+                    "{gutter} | {}{} {}",
+                    " ".repeat(offset),
+                    mark.repeat(width),
+                    label.message
+                );
+            }
+        }
+
+        if !self.notes.is_empty() || self.suggestion.is_some() {
+            let _ = writeln!(rendered, "{gutter} |");
+        }
+
+        for note in &self.notes {
+            let _ = writeln!(rendered, "{gutter} = note: {note}");
+        }
+
+        if let Some(suggestion) = &self.suggestion {
+            let _ = writeln!(
+                rendered,
+                "{gutter} = help: did you mean `{}`?",
+                suggestion.replacement
+            );
+        }
+
+        rendered
+    }
+}
+
+impl Calculator {
+    /// Find the problems of an input without calculating it, so variables arent changed by assignments
+    /// Unlike ```calculate``` this doesnt stop at the first problem, every unbalanced bracket and unknown name is reported
+    /// Problems which only show up while calculating, like a division by 0, arent found
+    pub fn check(&self, input: &str) -> Vec<Diagnostic> {
+        self.parse_with_recovery(input).diagnostics
+    }
+
+    /// Parse the input without stopping at the first problem, for example to highlight every problem of a long formula in an editor
+    /// The parts which couldnt be parsed become ```SyntaxKind::Error``` nodes of the tree, and every problem ```check``` finds is returned with it
+    pub fn parse_with_recovery(&self, input: &str) -> RecoveredParse {
+        let (tokens, errors) = tokenize_with_recovery(input);
+
+        let mut diagnostics: Vec<Diagnostic> =
+            errors.iter().map(|error| self.diagnostic(error)).collect();
+
+        diagnostics.extend(check_brackets(&tokens));
+
+        diagnostics.extend(self.check_names(&tokens, input));
+
+        let (tree, errors) = parser::parse_with_recovery(tokens, input, self);
+
+        //The brackets have already been checked, and unlike the parser check_brackets also points at where the ) is missing
+        diagnostics.extend(
+            errors
+                .iter()
+                .filter(|error| error.err_type != CalculatorErrorType::UnbalancedBracket)
+                .map(|error| self.diagnostic(error)),
+        );
+
+        diagnostics.sort_by_key(Diagnostic::start);
+
+        RecoveredParse {
+            tree: SyntaxNode::from(&tree),
+            diagnostics: remove_duplicates(diagnostics),
+        }
+    }
+
+    /// The problems of an input whose calculation has failed, the error comes first and is followed by the other problems ```check``` finds
+    pub fn diagnose(&self, error: &CalculatorError) -> Vec<Diagnostic> {
+        let mut problems = self.check(&error.input);
+
+        //Syntax errors are found by check too, its diagnostic for them has more details like where a bracket should be closed
+        let error = match problems
+            .iter()
+            .position(|problem| problem.primary_span() == Some(&error.span.range()))
+        {
+            Some(index) => problems.remove(index),
+            None => self.diagnostic(error),
+        };
+
+        remove_duplicates(std::iter::once(error).chain(problems).collect())
+    }
+
+    /// Turn a single error into a diagnostic
+    fn diagnostic(&self, error: &CalculatorError) -> Diagnostic {
+        let (label, note): (String, Option<&str>) = match &error.err_type {
+            CalculatorErrorType::UnknownVariable(name)
+            | CalculatorErrorType::UnknownFunction(name) => {
+                return self.unknown_name(error, name);
+            }
+            CalculatorErrorType::MissingArgumentBrackets(_) => (
+                "a function".into(),
+                Some("Functions are called with brackets, like `sin(0)`"),
+            ),
+            CalculatorErrorType::InvalidCharacter => ("unknown character".into(), None),
+            CalculatorErrorType::InvalidNumberLiteral => (
+                "invalid number".into(),
+                Some("Numbers look like `12`, `1.5`, `6.022e23` or `0xFF`, dates like `2026-10-18` and times like `17:30`"),
+            ),
+            CalculatorErrorType::UnbalancedBracket => ("unbalanced bracket".into(), None),
+            CalculatorErrorType::UnexpectedToken => ("unexpected".into(), None),
+            CalculatorErrorType::UnexpectedEndOfInput => ("the input ends here".into(), None),
+            CalculatorErrorType::InvalidConversion => ("not a unit".into(), None),
+            CalculatorErrorType::DivisionByZero | CalculatorErrorType::RemainderByZero => {
+                ("this is 0".into(), None)
+            }
+            CalculatorErrorType::Overflow => ("too large".into(), None),
+            CalculatorErrorType::ExactResultTooLarge => (
+                "too large to calculate exactly".into(),
+                Some("The float mode can calculate an approximation if the result fits into a float"),
+            ),
+            CalculatorErrorType::DomainError => ("undefined".into(), None),
+            CalculatorErrorType::InvalidBase => ("invalid base".into(), None),
+            CalculatorErrorType::InvalidPrecision => ("invalid precision".into(), None),
+            CalculatorErrorType::NonIntegerBitwiseOperand => ("not an integer".into(), None),
+            CalculatorErrorType::InvalidUnitPower => (
+                "the unit cant be raised to this power".into(),
+                Some("`(4 m^2)^0.5` is `2 m`, but `(2 m)^0.5` doesnt have a unit"),
+            ),
+            CalculatorErrorType::InvalidDateOperation => (
+                "not possible with dates".into(),
+                Some("`2026-10-18 + 45 days` and `2026-12-25 - 2026-10-18` work"),
+            ),
+            CalculatorErrorType::DimensionMismatch(..) => (
+                "the dimensions dont match".into(),
+                Some("Only units of the same kind can be added or converted, like `m` and `ft`"),
+            ),
+            CalculatorErrorType::MissingExchangeRate(_) => (
+                "no exchange rate".into(),
+                Some("Rates are loaded with `Calculator::set_exchange_rates` or `:rates rates.csv`"),
+            ),
+            CalculatorErrorType::ConstantAssignment(_) => ("a constant".into(), None),
+            CalculatorErrorType::ArgumentCount { found, .. } => {
+                (format!("called with {found} argument(s)"), None)
+            }
+            CalculatorErrorType::DuplicateParameter(_) => ("already a parameter".into(), None),
+            CalculatorErrorType::ReservedName(_) => ("an operator".into(), None),
+            CalculatorErrorType::FunctionRedefinition(_) => ("a builtin function".into(), None),
+            CalculatorErrorType::RecursionLimit(_) | CalculatorErrorType::NestingLimit(_) => {
+                ("nested too deep".into(), None)
+            }
+            CalculatorErrorType::DefinitionNotAllowed => ("a function definition".into(), None),
+            CalculatorErrorType::Warning(warning) => (
+                match warning {
+                    Warning::FloatFallback(_) => "not exact",
+                    Warning::DomainError(_) => "NaN",
+                    Warning::Infinity(_) | Warning::Overflow(_) => "infinite",
+                    Warning::PrecisionLoss { .. } => "mostly rounding errors",
+                }
+                .into(),
+                Some("Warnings are only errors when `Calculator::set_strict` is enabled"),
+            ),
+        };
+
+        Diagnostic::new(error.err_type.to_string())
+            .with_primary_label(error.span, label)
+            .with_note(note)
+    }
+
+    /// Names which arent variables, constants, units, currencies or functions, the name and the parameters of a function definition are known
+    fn check_names(&self, tokens: &[Token], input: &str) -> Vec<Diagnostic> {
+        let mut defined = Vec::new();
+
+        //Function bodies can call functions which are defined later
+        let mut is_definition = false;
+
+        if let Some(header) = function_header(tokens) {
+            defined.push(header.name);
+            defined.extend(header.parameters);
+
+            is_definition = true;
+        } else if let [Token {
+            kind: TokenKind::Identifier(name),
+            ..
+        }, Token {
+            kind: TokenKind::Assignment,
+            ..
+        }, ..] = tokens
+        {
+            defined.push(name.clone());
+        }
+
+        tokens
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                !is_definition
+                    || tokens
+                        .get(index + 1)
+                        .is_none_or(|next| next.kind != TokenKind::LeftBracket)
+            })
+            .filter_map(|(index, token)| match &token.kind {
+                TokenKind::Identifier(name) if !defined.contains(name) && !self.is_known(name) => {
+                    //A name followed by a bracket is called like a function, the same as the parser reports
+                    let error_type = match tokens.get(index + 1) {
+                        Some(Token {
+                            kind: TokenKind::LeftBracket,
+                            ..
+                        }) => CalculatorErrorType::UnknownFunction(name.clone()),
+                        _ => CalculatorErrorType::UnknownVariable(name.clone()),
+                    };
+
+                    Some(self.diagnostic(&CalculatorError::new(
+                        error_type,
+                        token.span,
+                        input.to_string(),
+                    )))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the name means something in a calculation
+    fn is_known(&self, name: &str) -> bool {
+        //5 xor 3, 5 km in m, 5 km to m
+        matches!(name, "xor" | "in" | "to")
+            || (name == "of" && self.percent_mode == PercentMode::Percentage)
+            || self.is_function(name)
+            || self.exchange_rates.is_currency(name)
+            || self.lookup(name).is_some()
+    }
+
+    /// Unknown names get a suggestion if they are close to a known name
+    fn unknown_name(&self, error: &CalculatorError, name: &str) -> Diagnostic {
+        let label = match error.err_type {
+            CalculatorErrorType::UnknownFunction(_) => "unknown function",
+            _ => "unknown variable",
+        };
+
+        let mut diagnostic =
+            Diagnostic::new(error.err_type.to_string()).with_primary_label(error.span, label);
+
+        diagnostic.suggestion = self
+            .similar_name(
+                name,
+                matches!(error.err_type, CalculatorErrorType::UnknownFunction(_)),
+            )
+            .map(|replacement| Suggestion {
+                span: error.span(),
+                replacement,
+            });
+
+        diagnostic
+    }
+
+    /// The known name which is the most similar to ```name```, if it is close enough to be a typo
+    /// Unknown functions are only compared with the names of functions
+    fn similar_name(&self, name: &str, function: bool) -> Option<String> {
+        //Short names are too similar to everything
+        let max_distance = name.chars().count() / 3;
+
+        let close_name = |candidate: &str| {
+            let distance = edit_distance(name, candidate);
+
+            (1..=max_distance)
+                .contains(&distance)
+                .then(|| (distance, candidate.to_string()))
+        };
+
+        let function_names = self
+            .functions
+            .keys()
+            .map(String::as_str)
+            .chain(BUILTIN_FUNCTIONS.iter().map(|function| function.name));
+
+        if function {
+            return function_names
+                .filter_map(close_name)
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, candidate)| candidate);
+        }
+
+        let user_names = self
+            .variables
+            .keys()
+            .map(String::as_str)
+            .chain(self.exchange_rates.currencies());
+
+        let builtin_names = constant_names().chain(unit_names());
+
+        user_names
+            .filter_map(close_name)
+            .chain(function_names.filter_map(close_name))
+            .chain(builtin_names.filter_map(close_name))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+}
+
+/// Keep only the first diagnostic of every part of the input, the later ones are about the same problem
+/// A lone ) is both a bracket which has never been opened and a token the parser didnt expect
+fn remove_duplicates(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut unique: Vec<Diagnostic> = Vec::with_capacity(diagnostics.len());
+
+    for diagnostic in diagnostics {
+        if !unique
+            .iter()
+            .any(|kept| kept.primary_span() == diagnostic.primary_span())
+        {
+            unique.push(diagnostic);
+        }
+    }
+
+    unique
+}
+
+/// Every ( which is never closed and every ) which has never been opened
+fn check_brackets(tokens: &[Token]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let mut open_brackets = Vec::new();
+
+    for token in tokens {
+        match token.kind {
+            TokenKind::LeftBracket => open_brackets.push(token.span),
+            //A ) closes the last (, unless every ( has been closed already
+            TokenKind::RightBracket if open_brackets.pop().is_none() => {
+                diagnostics.push(
+                    Diagnostic::new("The bracket has never been opened")
+                        .with_primary_label(token.span, "unmatched `)`"),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    //The missing ) belong after the last token
+    let end = tokens.last().map_or(0, |token| token.span.end);
+
+    for span in open_brackets {
+        diagnostics.push(
+            Diagnostic::new("The bracket is never closed")
+                .with_primary_label(span, "unclosed `(`")
+                .with_secondary_label(Span::new(end, end), "expected `)` here"),
+        );
+    }
+
+    diagnostics
+}
+
+/// The Levenshtein distance, the number of characters which have to be inserted, removed or replaced to turn ```a``` into ```b```
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    //The distances from the part of a seen so far to every prefix of b
+    let mut distances: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous_diagonal = distances[0];
+
+        distances[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_diagonal + usize::from(a_char != *b_char);
+
+            previous_diagonal = distances[j + 1];
+
+            distances[j + 1] = substitution.min(distances[j] + 1).min(distances[j + 1] + 1);
+        }
+    }
+
+    distances[b.len()]
+}
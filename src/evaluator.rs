@@ -0,0 +1,976 @@
+use chrono::{NaiveDate, Timelike};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::{
+    format::{format_clock, format_date},
+    functions::{
+        builtin_function, double_factorial, factorial, ArgumentRule, Arity, UnitRule,
+        MAX_BODY_DEPTH, MAX_CALL_DEPTH,
+    },
+    number::{shift_left, Number},
+    parser::{BinaryOperator, Node, NodeKind, PostfixOperator, UnaryOperator},
+    units::{Quantity, Unit, UnitKind},
+    Calculator, CalculatorError, CalculatorErrorType, RemainderMode, Result, Warning,
+};
+
+/// Additions and subtractions of floats which lose at least this many of their about 16 significant digits are warned about
+const PRECISION_LOSS_WARNING_DIGITS: f64 = 8.;
+
+/// Times of the day go around the clock after this many seconds
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// Floats whose last this many bits are 0 were written exactly, like integers and halves, a rounded float only ends like this once in 256 times
+const EXACT_FLOAT_ZERO_BITS: u32 = 8;
+
+impl Calculator {
+    /// Calculate the value of the expression tree by walking it once
+    /// Every node is calculated in its own method, so the stack frame of this recursive method stays small
+    pub(crate) fn evaluate(&mut self, node: &Node, input: &str) -> Result<Quantity> {
+        match &node.kind {
+            NodeKind::Number(_) | NodeKind::Date(_) | NodeKind::Time(_) => {
+                self.literal(node, input)
+            }
+            NodeKind::Variable(name) => self.variable(name, node, input),
+            NodeKind::Unary { operator, operand } => {
+                let value = self.evaluate(operand, input)?;
+
+                self.unary(*operator, value, operand, node, input)
+            }
+            NodeKind::Postfix { operator, operand } => {
+                let value = self.evaluate(operand, input)?;
+
+                self.postfix(*operator, value, operand, node, input)
+            }
+            NodeKind::Binary { .. } => self.binary(node, input),
+            NodeKind::Call { name, arguments } => self.call(name, arguments, node, input),
+            //Trees with errors can only be built when recovering, they cant be calculated
+            NodeKind::Error(error_type) => Err(CalculatorError::new(
+                error_type.clone(),
+                node.span,
+                input.to_string(),
+            )),
+            //Definitions are handled by the calculator before evaluation
+            NodeKind::FunctionDefinition { .. } => Err(CalculatorError::new(
+                CalculatorErrorType::DefinitionNotAllowed,
+                node.span,
+                input.to_string(),
+            )),
+            NodeKind::Assignment { name, value } => {
+                if self.constant(name).is_some() {
+                    return Err(CalculatorError::new(
+                        CalculatorErrorType::ConstantAssignment(name.clone()),
+                        node.span,
+                        input.to_string(),
+                    ));
+                }
+
+                let value = self.evaluate(value, input)?;
+
+                //Only store the variable if the whole calculation was successful
+                self.variables.insert(name.clone(), value.clone());
+
+                Ok(value)
+            }
+        }
+    }
+
+    /// The value of a number, date or time literal
+    fn literal(&mut self, node: &Node, input: &str) -> Result<Quantity> {
+        Ok(match &node.kind {
+            NodeKind::Date(date) => Quantity::new(
+                Number::from_integer(
+                    date.signed_duration_since(NaiveDate::default()).num_days(),
+                    self.number_mode,
+                ),
+                Unit::date(),
+            ),
+            NodeKind::Time(time) => Quantity::new(
+                Number::from_integer(time.num_seconds_from_midnight().into(), self.number_mode),
+                Unit::clock(),
+            ),
+            NodeKind::Number(literal) => {
+                let number = Number::from_literal(literal, self.number_mode);
+
+                //Literals are always finite, but ones like 1e400 are too large for a float
+                if number.is_infinite() {
+                    self.warn(
+                        Warning::Overflow(input[node.span.range()].to_string()),
+                        node,
+                        input,
+                    )?;
+                }
+
+                //Exponents like 1e100000000 are too large for exact fractions
+                self.check_float_fallback(self.number_mode.is_exact(), &number, node, input)?;
+
+                number.into()
+            }
+            _ => unreachable!("Only literals are passed here"),
+        })
+    }
+
+    /// The value of a variable, a constant, a unit or a currency
+    fn variable(&mut self, name: &str, node: &Node, input: &str) -> Result<Quantity> {
+        match self.lookup(name) {
+            Some(value) => {
+                //Constants like pi are floats
+                self.check_float_fallback(self.number_mode.is_exact(), &value.number, node, input)?;
+
+                Ok(value)
+            }
+            None if self.exchange_rates.is_currency(name) => Err(CalculatorError::new(
+                CalculatorErrorType::MissingExchangeRate(name.to_string()),
+                node.span,
+                input.to_string(),
+            )),
+            None => Err(CalculatorError::new(
+                CalculatorErrorType::UnknownVariable(name.to_string()),
+                node.span,
+                input.to_string(),
+            )),
+        }
+    }
+
+    /// Apply a unary operator to the value of its operand
+    fn unary(
+        &self,
+        operator: UnaryOperator,
+        value: Quantity,
+        operand: &Node,
+        node: &Node,
+        input: &str,
+    ) -> Result<Quantity> {
+        //A date cant be negated, but a time of the day can: -17:30 is 17:30 before midnight
+        if value.unit.is_date() && operator != UnaryOperator::Plus {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::InvalidDateOperation,
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        let result = match operator {
+            UnaryOperator::Minus => -value,
+            UnaryOperator::Plus => value,
+            UnaryOperator::BitwiseNot => {
+                Number::from_bigint(!self.to_integer(value, operand, input)?, self.number_mode)
+                    .into()
+            }
+        };
+
+        self.check_date_and_time(result, node, input)
+    }
+
+    /// Apply a postfix operator to the value of its operand
+    fn postfix(
+        &mut self,
+        operator: PostfixOperator,
+        value: Quantity,
+        operand: &Node,
+        node: &Node,
+        input: &str,
+    ) -> Result<Quantity> {
+        if value.unit.is_date() {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::InvalidDateOperation,
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        let value = match operator {
+            //10% of a length is still a length
+            PostfixOperator::Percent => {
+                return Ok(Quantity::new(
+                    value.number.div(
+                        Number::from_integer(100, self.number_mode),
+                        &self.decimal_context,
+                    ),
+                    value.unit,
+                ))
+            }
+            PostfixOperator::Factorial | PostfixOperator::DoubleFactorial => {
+                self.to_number(value, operand, input)?
+            }
+        };
+
+        let step = match operator {
+            PostfixOperator::Factorial => 1,
+            PostfixOperator::DoubleFactorial => 2,
+            //Percentages have returned above
+            PostfixOperator::Percent => unreachable!(),
+        };
+
+        let exact_result = value
+            .exact_factorial(step, &self.decimal_context)
+            .map_err(|error_type| CalculatorError::new(error_type, node.span, input.to_string()))?;
+
+        //Fall back to floats if the factorial cant be multiplied out exactly
+        let result = exact_result.unwrap_or_else(|| {
+            Number::Float(match operator {
+                PostfixOperator::Factorial => factorial(value.to_f64()),
+                _ => double_factorial(value.to_f64()),
+            })
+        });
+
+        self.check_float_fallback(value.is_exact(), &result, node, input)?;
+
+        //The factorial of a negative integer doesnt exist
+        if result.is_nan() && !value.is_nan() {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::DomainError,
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        if result.is_infinite() && !value.is_infinite() {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::Overflow,
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        Ok(result.into())
+    }
+
+    /// Calculate a chain of binary operators like ```1 + 2 + 3```
+    /// The left operands are walked down in a loop and only the right operands recurse, so long chains dont overflow the stack
+    fn binary(&mut self, node: &Node, input: &str) -> Result<Quantity> {
+        //The nodes of the chain from the root down, with their right operands
+        let mut chain = Vec::new();
+        let mut leftmost = node;
+
+        while let NodeKind::Binary { lhs, rhs, .. } = &leftmost.kind {
+            chain.push((leftmost, rhs));
+            leftmost = lhs;
+        }
+
+        let mut value = self.evaluate(leftmost, input)?;
+
+        for (node, rhs) in chain.into_iter().rev() {
+            let rhs_value = self.evaluate(rhs, input)?;
+
+            value = self.apply_binary(value, rhs_value, node, input)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Apply the operator of a binary node to the values of its operands
+    /// This doesnt recurse, so its large stack frame isnt kept while the operands are calculated
+    fn apply_binary(
+        &mut self,
+        mut lhs_value: Quantity,
+        mut rhs_value: Quantity,
+        node: &Node,
+        input: &str,
+    ) -> Result<Quantity> {
+        let NodeKind::Binary { operator, lhs, rhs } = &node.kind else {
+            unreachable!("Only binary nodes have binary operators")
+        };
+
+        let operator = *operator;
+
+        self.check_dates(operator, &lhs_value, &rhs_value, node, input)?;
+
+        //45 days + 2026-10-18 is calculated like 2026-10-18 + 45 days
+        let (lhs, rhs) = if rhs_value.unit.is_date() && operator == BinaryOperator::Addition {
+            std::mem::swap(&mut lhs_value, &mut rhs_value);
+
+            (rhs, lhs)
+        } else {
+            (lhs, rhs)
+        };
+
+        //The difference of two dates is a number of days
+        let date_difference = lhs_value.unit.is_date() && rhs_value.unit.is_date();
+
+        //200 + 10% adds 10% of 200, the same goes for -
+        if !lhs_value.unit.is_date()
+            && is_percentage(rhs)
+            && matches!(
+                operator,
+                BinaryOperator::Addition | BinaryOperator::Subtraction
+            )
+        {
+            rhs_value = rhs_value.mul(lhs_value.clone(), self.number_mode, &self.decimal_context);
+        }
+
+        let operands = [lhs_value.number.to_complex(), rhs_value.number.to_complex()];
+
+        let result = match operator {
+            //The right side is converted to the unit of the left side: 3 ft + 20 cm is in ft
+            BinaryOperator::Addition => {
+                let rhs_value = self.convert(rhs_value, &lhs_value.unit, rhs, input)?;
+
+                let error = rounding_error(&lhs_value.number) + rounding_error(&rhs_value.number);
+
+                let sum = lhs_value
+                    .number
+                    .add(rhs_value.number, &self.decimal_context);
+
+                self.check_cancellation(error, &sum, node, input)?;
+
+                Quantity::new(sum, lhs_value.unit)
+            }
+            BinaryOperator::Subtraction => {
+                let rhs_value = self.convert(rhs_value, &lhs_value.unit, rhs, input)?;
+
+                //Both are in days since 1970-01-01
+                let unit = if date_difference {
+                    Unit::days()
+                } else {
+                    lhs_value.unit
+                };
+
+                let error = rounding_error(&lhs_value.number) + rounding_error(&rhs_value.number);
+
+                let difference = lhs_value
+                    .number
+                    .sub(rhs_value.number, &self.decimal_context);
+
+                self.check_cancellation(error, &difference, node, input)?;
+
+                Quantity::new(difference, unit)
+            }
+            BinaryOperator::Multiplication => {
+                lhs_value.mul(rhs_value, self.number_mode, &self.decimal_context)
+            }
+            BinaryOperator::Division => {
+                if rhs_value.number.is_zero() {
+                    return Err(CalculatorError::new(
+                        CalculatorErrorType::DivisionByZero,
+                        rhs.span,
+                        input.to_string(),
+                    ));
+                }
+
+                let integers = matches!(
+                    (&lhs_value.number, &rhs_value.number),
+                    (Number::Integer(_), Number::Integer(_))
+                );
+
+                let quotient = lhs_value.div(rhs_value, self.number_mode, &self.decimal_context);
+
+                //Integers only divide exactly, 7 / 2 has no integer result
+                if integers && matches!(quotient.number, Number::Rational(_)) {
+                    return Err(CalculatorError::new(
+                        CalculatorErrorType::DomainError,
+                        node.span,
+                        input.to_string(),
+                    ));
+                }
+
+                quotient
+            }
+            BinaryOperator::Modulo => {
+                if rhs_value.number.is_zero() {
+                    return Err(CalculatorError::new(
+                        CalculatorErrorType::RemainderByZero,
+                        rhs.span,
+                        input.to_string(),
+                    ));
+                }
+
+                //The remainder is only defined for real numbers
+                for (value, node) in [(&lhs_value, lhs), (&rhs_value, rhs)] {
+                    if !value.number.is_real() {
+                        return Err(CalculatorError::new(
+                            CalculatorErrorType::DomainError,
+                            node.span,
+                            input.to_string(),
+                        ));
+                    }
+                }
+
+                let rhs_value = self.convert(rhs_value, &lhs_value.unit, rhs, input)?;
+
+                Quantity::new(
+                    lhs_value.number.rem(rhs_value.number, self.remainder_mode),
+                    lhs_value.unit,
+                )
+            }
+            BinaryOperator::Power => {
+                let exponent = self.to_number(rhs_value, rhs, input)?;
+
+                let exact_operands = lhs_value.number.is_exact() && exponent.is_exact();
+
+                let Some(power) = lhs_value.pow(exponent, &self.decimal_context) else {
+                    return Err(CalculatorError::new(
+                        CalculatorErrorType::InvalidUnitPower,
+                        rhs.span,
+                        input.to_string(),
+                    ));
+                };
+
+                self.check_float_fallback(exact_operands, &power.number, node, input)?;
+
+                power
+            }
+            BinaryOperator::BitwiseAnd => Number::from_bigint(
+                self.to_integer(lhs_value, lhs, input)? & self.to_integer(rhs_value, rhs, input)?,
+                self.number_mode,
+            )
+            .into(),
+            BinaryOperator::BitwiseOr => Number::from_bigint(
+                self.to_integer(lhs_value, lhs, input)? | self.to_integer(rhs_value, rhs, input)?,
+                self.number_mode,
+            )
+            .into(),
+            BinaryOperator::BitwiseXor => Number::from_bigint(
+                self.to_integer(lhs_value, lhs, input)? ^ self.to_integer(rhs_value, rhs, input)?,
+                self.number_mode,
+            )
+            .into(),
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                let lhs_integer = self.to_integer(lhs_value, lhs, input)?;
+                let shift = self.to_integer(rhs_value, rhs, input)?;
+
+                //Shifting by a negative amount isnt defined and shifts beyond u64 cant fit into memory
+                let Some(shift) = shift.to_u64() else {
+                    return Err(CalculatorError::new(
+                        if shift.is_negative() {
+                            CalculatorErrorType::DomainError
+                        } else {
+                            CalculatorErrorType::Overflow
+                        },
+                        rhs.span,
+                        input.to_string(),
+                    ));
+                };
+
+                let shifted = if operator == BinaryOperator::ShiftLeft {
+                    match shift_left(&lhs_integer, shift) {
+                        Some(shifted) => shifted,
+                        None => {
+                            return Err(CalculatorError::new(
+                                CalculatorErrorType::Overflow,
+                                node.span,
+                                input.to_string(),
+                            ))
+                        }
+                    }
+                } else {
+                    lhs_integer >> shift
+                };
+
+                Number::from_bigint(shifted, self.number_mode).into()
+            }
+            BinaryOperator::Conversion => {
+                //The right side has to be a unit without a number, like km/h
+                if rhs_value.number.to_f64() != 1. {
+                    return Err(CalculatorError::new(
+                        CalculatorErrorType::InvalidConversion,
+                        rhs.span,
+                        input.to_string(),
+                    ));
+                }
+
+                self.convert(lhs_value, &rhs_value.unit, rhs, input)?
+            }
+        };
+
+        self.check_special_values(&operands, &result.number, node, input)?;
+
+        self.check_date_and_time(result, node, input)
+    }
+
+    /// Call a builtin or a user defined function
+    /// This is kept out of ```evaluate``` so the stack frame of ```evaluate``` stays small during recursion
+    fn call(
+        &mut self,
+        name: &str,
+        arguments: &[Node],
+        node: &Node,
+        input: &str,
+    ) -> Result<Quantity> {
+        if let Some(function) = builtin_function(name) {
+            self.check_argument_count(name, function.arity, arguments.len(), node, input)?;
+
+            let argument_values = self.evaluate_arguments(arguments, input)?;
+
+            let (argument_values, unit) =
+                self.apply_unit_rule(function.units, argument_values, arguments, node, input)?;
+
+            check_argument_rule(function.arguments, &argument_values, arguments, input)?;
+
+            if function.arguments == ArgumentRule::Base {
+                self.called_base = Some(argument_values[1].to_f64() as u32);
+            }
+
+            //Floats dont need the other version
+            let number_result = match function.number_function {
+                Some(number_function) if !argument_values.iter().all(Number::is_float) => {
+                    number_function(&argument_values, &self.decimal_context)
+                }
+                _ => None,
+            };
+
+            let result = number_result.unwrap_or_else(|| {
+                let float_arguments: Vec<f64> =
+                    argument_values.iter().map(Number::to_f64).collect();
+
+                Number::Float((function.function)(&float_arguments))
+            });
+
+            self.check_float_fallback(
+                argument_values.iter().all(Number::is_exact),
+                &result,
+                node,
+                input,
+            )?;
+
+            let arguments: Vec<Complex64> =
+                argument_values.iter().map(Number::to_complex).collect();
+
+            self.check_special_values(&arguments, &result, node, input)?;
+
+            //min and max of dates are dates too, abs of a time of the day is one too
+            return self.check_date_and_time(Quantity::new(result, unit), node, input);
+        }
+
+        //Calls in function bodies can name functions which are defined later, they may still not exist
+        let Some(function) = self.functions.get(name).cloned() else {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::UnknownFunction(name.to_string()),
+                node.span,
+                input.to_string(),
+            ));
+        };
+
+        self.check_argument_count(
+            name,
+            Arity::Exact(function.parameters.len()),
+            arguments.len(),
+            node,
+            input,
+        )?;
+
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::RecursionLimit(MAX_CALL_DEPTH),
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        //Functions with deep bodies could overflow the stack before the call depth is reached
+        if self.body_depth + function.body.depth > MAX_BODY_DEPTH {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::NestingLimit(MAX_BODY_DEPTH),
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        let argument_values = self.evaluate_arguments(arguments, input)?;
+
+        self.call_stack.push(
+            function
+                .parameters
+                .iter()
+                .cloned()
+                .zip(argument_values)
+                .collect(),
+        );
+
+        self.body_depth += function.body.depth;
+
+        //The errors inside the body point into the definition of the function
+        let result = self.evaluate(&function.body, &function.source);
+
+        //Remove the arguments even if the call has failed
+        self.call_stack.pop();
+        self.body_depth -= function.body.depth;
+
+        result
+    }
+
+    /// Warn that the answer isnt exact if an operation on exact numbers has resulted in a float, like ```sqrt(2)``` in ```NumberMode::Rational```
+    fn check_float_fallback(
+        &mut self,
+        exact_operands: bool,
+        result: &Number,
+        node: &Node,
+        input: &str,
+    ) -> Result<()> {
+        if !exact_operands || result.is_exact() {
+            return Ok(());
+        }
+
+        self.warn(
+            Warning::FloatFallback(input[node.span.range()].to_string()),
+            node,
+            input,
+        )
+    }
+
+    /// Warn if the result is NaN or infinite although the operands werent, like ```(-1)^0.5```, ```0^-1``` or ```10^400```
+    /// The operands are complex so the imaginary parts are checked too, real numbers just have an imaginary part of 0
+    fn check_special_values(
+        &mut self,
+        operands: &[Complex64],
+        result: &Number,
+        node: &Node,
+        input: &str,
+    ) -> Result<()> {
+        //A NaN next to an infinite part comes from calculating with infinities, like in (1e200 + 1e200i)^2, so the result is infinite too
+        let infinite = result.is_infinite()
+            || (result.is_nan() && {
+                let result = result.to_complex();
+
+                result.re.is_infinite() || result.im.is_infinite()
+            });
+
+        //The expression is only copied out of the input once there is a warning, copying it for every node of a long chain would take quadratic time
+        let warning: fn(String) -> Warning =
+            if result.is_nan() && !infinite && !operands.iter().any(|operand| operand.is_nan()) {
+                Warning::DomainError
+            } else if infinite && operands.iter().all(|operand| operand.is_finite()) {
+                //Without a 0 the operands were just too large, like 1e200 * 1e200
+                if operands.iter().any(Complex64::is_zero) {
+                    Warning::Infinity
+                } else {
+                    Warning::Overflow
+                }
+            } else {
+                return Ok(());
+            };
+
+        self.warn(warning(input[node.span.range()].to_string()), node, input)
+    }
+
+    /// Warn if an addition or subtraction of floats has cancelled most of their digits
+    /// ```error``` is the largest rounding error the operands can carry, the digits of the result below it are noise
+    fn check_cancellation(
+        &mut self,
+        error: f64,
+        result: &Number,
+        node: &Node,
+        input: &str,
+    ) -> Result<()> {
+        //Exact numbers have no rounding errors to lose digits to
+        if self.number_mode.is_exact() {
+            return Ok(());
+        }
+
+        let result = result.to_f64().abs();
+
+        //A result of exactly 0 is most likely right, like x - x
+        if result == 0. || !result.is_finite() || error == 0. || !error.is_finite() {
+            return Ok(());
+        }
+
+        //How much larger the error is than the rounding error the result would have on its own
+        let lost_digits = (error / (result * f64::EPSILON / 2.)).log10().floor();
+
+        if lost_digits < PRECISION_LOSS_WARNING_DIGITS {
+            return Ok(());
+        }
+
+        self.warn(
+            Warning::PrecisionLoss {
+                expression: input[node.span.range()].to_string(),
+                lost_digits: lost_digits as u32,
+            },
+            node,
+            input,
+        )
+    }
+
+    /// Add the warning to the answer, or return it as an error if the calculator is strict
+    fn warn(&mut self, warning: Warning, node: &Node, input: &str) -> Result<()> {
+        if self.strict {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::Warning(warning),
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+
+        Ok(())
+    }
+
+    /// Convert the arguments of a builtin function to plain numbers as its ```UnitRule``` says, the unit of the result is returned with them
+    fn apply_unit_rule(
+        &self,
+        rule: UnitRule,
+        argument_values: Vec<Quantity>,
+        arguments: &[Node],
+        node: &Node,
+        input: &str,
+    ) -> Result<(Vec<Number>, Unit)> {
+        let unit = match rule {
+            UnitRule::Dimensionless => Unit::default(),
+            UnitRule::Same => argument_values[0].unit.clone(),
+            UnitRule::Dates => {
+                let mut numbers = Vec::new();
+
+                for (value, argument) in argument_values.into_iter().zip(arguments) {
+                    if !value.unit.is_date() {
+                        return Err(CalculatorError::new(
+                            CalculatorErrorType::InvalidDateOperation,
+                            argument.span,
+                            input.to_string(),
+                        ));
+                    }
+
+                    numbers.push(value.number);
+                }
+
+                return Ok((numbers, Unit::days()));
+            }
+            UnitRule::Root(n) => {
+                let Some(unit) = argument_values[0].unit.root(n) else {
+                    return Err(CalculatorError::new(
+                        CalculatorErrorType::InvalidUnitPower,
+                        node.span,
+                        input.to_string(),
+                    ));
+                };
+
+                let numbers = argument_values
+                    .into_iter()
+                    .map(|value| value.number)
+                    .collect();
+
+                return Ok((numbers, unit));
+            }
+        };
+
+        let numbers = argument_values
+            .into_iter()
+            .zip(arguments)
+            .map(|(value, argument)| Ok(self.convert(value, &unit, argument, input)?.number))
+            .collect::<Result<Vec<Number>>>()?;
+
+        Ok((numbers, unit))
+    }
+
+    /// Dates have to be in the range chrono supports and times of the day have to be finite, otherwise they cant be displayed like ```2026-10-18``` or ```17:30```
+    /// Times of the day go around the clock like on a watch: ```23:59 + 1 min``` is ```0:00``` and ```-17:30``` is ```6:30```
+    fn check_date_and_time(&self, value: Quantity, node: &Node, input: &str) -> Result<Quantity> {
+        let number = value.number.to_f64();
+
+        let displayable = match value.unit.kind() {
+            UnitKind::Date => format_date(number).is_some(),
+            UnitKind::Clock => format_clock(number).is_some(),
+            UnitKind::Measure => return Ok(value),
+        };
+
+        if !displayable {
+            return Err(CalculatorError::new(
+                if number.is_nan() {
+                    CalculatorErrorType::DomainError
+                } else {
+                    CalculatorErrorType::Overflow
+                },
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        if value.unit.kind() == UnitKind::Date {
+            return Ok(value);
+        }
+
+        Ok(Quantity::new(
+            value.number.rem(
+                Number::from_integer(SECONDS_PER_DAY, self.number_mode),
+                RemainderMode::Euclidean,
+            ),
+            value.unit,
+        ))
+    }
+
+    /// Dates can only be moved by a duration like ```2026-10-18 + 45 days```, subtracted from each other or converted to other dates
+    fn check_dates(
+        &self,
+        operator: BinaryOperator,
+        lhs_value: &Quantity,
+        rhs_value: &Quantity,
+        node: &Node,
+        input: &str,
+    ) -> Result<()> {
+        let allowed = match (operator, lhs_value.unit.is_date(), rhs_value.unit.is_date()) {
+            (_, false, false) => true,
+            (BinaryOperator::Addition, lhs_date, rhs_date) => lhs_date != rhs_date,
+            (BinaryOperator::Subtraction, lhs_date, _) => lhs_date,
+            (BinaryOperator::Conversion, lhs_date, rhs_date) => lhs_date == rhs_date,
+            _ => false,
+        };
+
+        if !allowed {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::InvalidDateOperation,
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Convert a value to another unit, ```node``` is the node the value came from
+    fn convert(&self, value: Quantity, unit: &Unit, node: &Node, input: &str) -> Result<Quantity> {
+        if value.unit.dimension() != unit.dimension() {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::DimensionMismatch(
+                    describe_unit(&value.unit),
+                    describe_unit(unit),
+                ),
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        Ok(value.convert(unit, self.number_mode, &self.decimal_context))
+    }
+
+    /// Convert a value to a plain number for the operations which dont work with units, like ```5!```
+    fn to_number(&self, value: Quantity, node: &Node, input: &str) -> Result<Number> {
+        Ok(self.convert(value, &Unit::default(), node, input)?.number)
+    }
+
+    /// Convert a value to an integer for the bitwise operators, ```node``` is the node the value came from
+    fn to_integer(&self, value: Quantity, node: &Node, input: &str) -> Result<BigInt> {
+        self.to_number(value, node, input)?
+            .to_bitwise_integer()
+            .map_err(|error_type| CalculatorError::new(error_type, node.span, input.to_string()))
+    }
+
+    fn evaluate_arguments(&mut self, arguments: &[Node], input: &str) -> Result<Vec<Quantity>> {
+        arguments
+            .iter()
+            .map(|argument| self.evaluate(argument, input))
+            .collect()
+    }
+
+    fn check_argument_count(
+        &self,
+        name: &str,
+        arity: Arity,
+        argument_count: usize,
+        node: &Node,
+        input: &str,
+    ) -> Result<()> {
+        if !arity.accepts(argument_count) {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::ArgumentCount {
+                    name: name.to_string(),
+                    expected: arity,
+                    found: argument_count,
+                },
+                node.span,
+                input.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether the node is a percentage like ```10%```, also with a sign like ```-10%```
+fn is_percentage(mut node: &Node) -> bool {
+    while let NodeKind::Unary {
+        operator: UnaryOperator::Minus | UnaryOperator::Plus,
+        operand,
+    } = &node.kind
+    {
+        node = operand;
+    }
+
+    matches!(
+        node.kind,
+        NodeKind::Postfix {
+            operator: PostfixOperator::Percent,
+            ..
+        }
+    )
+}
+
+/// Check the arguments of a builtin function against its ```ArgumentRule```, the error points at the first argument which doesnt fit
+fn check_argument_rule(
+    rule: ArgumentRule,
+    argument_values: &[Number],
+    arguments: &[Node],
+    input: &str,
+) -> Result<()> {
+    let error = |error_type, argument: &Node| {
+        Err(CalculatorError::new(
+            error_type,
+            argument.span,
+            input.to_string(),
+        ))
+    };
+
+    let fits: fn(&Number) -> bool = match rule {
+        ArgumentRule::Any => return Ok(()),
+        ArgumentRule::Real | ArgumentRule::Base => Number::is_real,
+        ArgumentRule::Integers | ArgumentRule::ModularPower => Number::is_integer,
+    };
+
+    for (value, argument) in argument_values.iter().zip(arguments) {
+        if !fits(value) {
+            return error(CalculatorErrorType::DomainError, argument);
+        }
+    }
+
+    if rule == ArgumentRule::ModularPower {
+        //The power would be a fraction, and there is no remainder of a division by 0
+        if argument_values[1].to_f64() < 0. {
+            return error(CalculatorErrorType::DomainError, &arguments[1]);
+        }
+
+        if argument_values[2].is_zero() {
+            return error(CalculatorErrorType::RemainderByZero, &arguments[2]);
+        }
+    }
+
+    if rule == ArgumentRule::Base {
+        let base = argument_values[1].to_f64();
+
+        if base.fract() != 0. || !(2. ..=36.).contains(&base) {
+            return error(CalculatorErrorType::InvalidBase, &arguments[1]);
+        }
+    }
+
+    Ok(())
+}
+
+/// How a unit is shown in the errors
+fn describe_unit(unit: &Unit) -> String {
+    if unit.is_empty() {
+        "a plain number".to_string()
+    } else {
+        format!("`{unit}`")
+    }
+}
+
+/// The largest rounding error an operand can carry, half of its last bit
+/// Exact numbers and floats which end in 0 bits, like ```123456789``` or ```0.5```, were never rounded
+fn rounding_error(number: &Number) -> f64 {
+    if number.is_exact() {
+        return 0.;
+    }
+
+    let value = number.to_f64().abs();
+    let bits = value.to_bits();
+
+    //The 52 bits after the point, with the 1 before the point which isnt stored
+    let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+
+    if value == 0. || mantissa.trailing_zeros() >= EXACT_FLOAT_ZERO_BITS {
+        0.
+    } else {
+        (f64::from_bits(bits + 1) - value) / 2.
+    }
+}
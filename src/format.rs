@@ -0,0 +1,145 @@
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use num_bigint::BigInt;
+use num_traits::Signed;
+
+/// The digits used for the bases up to 36
+const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// How many digits are shown after the point when the number isnt an integer
+const MAX_FRACTION_DIGITS: usize = 16;
+
+/// Times of the day and durations with more seconds than this cant be shown like a clock, floats arent exact to the second anymore
+const MAX_CLOCK_SECONDS: f64 = 9_007_199_254_740_992.;
+
+/// The prefix of a number in a base, like the literals have
+fn prefix(base: u32) -> &'static str {
+    match base {
+        2 => "0b",
+        8 => "0o",
+        16 => "0x",
+        _ => "",
+    }
+}
+
+/// Bases without a prefix are written after the number
+fn suffix(base: u32) -> String {
+    match base {
+        2 | 8 | 16 => String::new(),
+        _ => format!(" (base {base})"),
+    }
+}
+
+/// Render a number in any base between 2 and 36
+/// Bases 2, 8 and 16 get the same prefix as the literals (0b, 0o, 0x), any other base except 10 gets a suffix like ```ZZ (base 36)```
+pub(crate) fn format_in_base(value: f64, base: u32) -> String {
+    if base == 10 || !value.is_finite() {
+        return value.to_string();
+    }
+
+    let mut formatted = String::new();
+
+    if value < 0. {
+        formatted.push('-');
+    }
+
+    formatted.push_str(prefix(base));
+
+    let value = value.abs();
+
+    let mut integer_part = value.trunc();
+
+    //Collect the digits of the integer part from the last one
+    let mut integer_digits = Vec::new();
+
+    loop {
+        integer_digits.push(DIGITS[(integer_part % base as f64) as usize]);
+
+        integer_part = (integer_part / base as f64).trunc();
+
+        if integer_part == 0. {
+            break;
+        }
+    }
+
+    formatted.extend(integer_digits.iter().rev().map(|digit| *digit as char));
+
+    let mut fraction = value.fract();
+
+    if fraction != 0. {
+        formatted.push('.');
+
+        for _ in 0..MAX_FRACTION_DIGITS {
+            fraction *= base as f64;
+
+            formatted.push(DIGITS[fraction.trunc() as usize] as char);
+
+            fraction = fraction.fract();
+
+            if fraction == 0. {
+                break;
+            }
+        }
+    }
+
+    formatted.push_str(&suffix(base));
+
+    formatted
+}
+
+/// Render an integer of any size in any base between 2 and 36, with the same prefixes and suffixes as ```format_in_base```
+pub(crate) fn format_integer_in_base(value: &BigInt, base: u32) -> String {
+    if base == 10 {
+        return value.to_string();
+    }
+
+    let sign = if value.is_negative() { "-" } else { "" };
+
+    let digits = value.magnitude().to_str_radix(base).to_uppercase();
+
+    format!("{sign}{}{digits}{}", prefix(base), suffix(base))
+}
+
+/// Render a number of days since 1970-01-01 as a date like ```2026-12-02```, the time is only added if the day has started: ```2026-10-18 12:00```
+/// None if the date is out of the range chrono supports
+pub(crate) fn format_date(days: f64) -> Option<String> {
+    if !days.is_finite() || days.abs() > i64::MAX as f64 / 86400. {
+        return None;
+    }
+
+    let seconds = (days * 86400.).round() as i64;
+
+    let date_time = NaiveDateTime::from(NaiveDate::default())
+        .checked_add_signed(TimeDelta::try_seconds(seconds)?)?;
+
+    if seconds % 86400 == 0 {
+        return Some(date_time.format("%Y-%m-%d").to_string());
+    }
+
+    Some(format!(
+        "{} {}",
+        date_time.format("%Y-%m-%d"),
+        format_clock(seconds.rem_euclid(86400) as f64)?
+    ))
+}
+
+/// Render a number of seconds like a clock: ```17:30``` or ```8:15:30```
+/// None if the number isnt finite or it has too many seconds to be exact
+pub(crate) fn format_clock(seconds: f64) -> Option<String> {
+    if !seconds.is_finite() || seconds.abs() > MAX_CLOCK_SECONDS {
+        return None;
+    }
+
+    let sign = if seconds < 0. { "-" } else { "" };
+
+    let seconds = seconds.abs().round();
+
+    let hours = (seconds / 3600.).trunc();
+    let minutes = (seconds % 3600. / 60.).trunc();
+    let seconds = seconds % 60.;
+
+    Some(if seconds == 0. {
+        format!("{sign}{hours}:{minutes:02}")
+    } else {
+        format!("{sign}{hours}:{minutes:02}:{seconds:02}")
+    })
+}
@@ -0,0 +1,627 @@
+use std::fmt::Display;
+
+use bigdecimal::{Context, RoundingMode};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+
+use crate::{
+    number::Number,
+    parser::{Node, MAX_NESTING_DEPTH},
+};
+
+/// How deep user defined functions can call each other, this stops endless recursion like ```f(x) = f(x)```
+pub(crate) const MAX_CALL_DEPTH: usize = 64;
+
+/// How deep the bodies of the functions being called can be nested together, the evaluator recurses this deep on top of the input
+/// This is large enough for most recursive functions to reach ```MAX_CALL_DEPTH``` first
+pub(crate) const MAX_BODY_DEPTH: usize = MAX_NESTING_DEPTH * 16;
+
+/// How many arguments a function accepts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+    Exact(usize),
+    /// Both ends are inclusive
+    Range(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(self, argument_count: usize) -> bool {
+        match self {
+            Arity::Exact(count) => argument_count == count,
+            Arity::Range(min, max) => (min..=max).contains(&argument_count),
+            Arity::AtLeast(min) => argument_count >= min,
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arity::Exact(count) => write!(f, "{count}"),
+            Arity::Range(min, max) => write!(f, "{min} to {max}"),
+            Arity::AtLeast(min) => write!(f, "at least {min}"),
+        }
+    }
+}
+
+/// What a builtin function does with the units of its arguments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum UnitRule {
+    /// The arguments are converted to plain numbers, so ```sin(90 deg)``` works but ```sin(5 m)``` is an error
+    Dimensionless,
+    /// The arguments are converted to the unit of the first one, which is also the unit of the result: ```max(1 m, 20 cm) = 1 m```
+    Same,
+    /// The unit of the result is the nth root of the unit of the argument: ```sqrt(4 m^2) = 2 m```
+    Root(i32),
+    /// The arguments have to be dates, they are passed as days since 1970-01-01 and the result is in days
+    Dates,
+}
+
+/// Which values the arguments of a builtin function can have, other values are errors instead of a NaN result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ArgumentRule {
+    Any,
+    /// Real numbers, complex numbers cant be ordered like in ```max(1, 2)```
+    Real,
+    /// Integers of any size, like in ```gcd(12, 18)```
+    Integers,
+    /// ```powmod(a, b, m)``` needs integers, ```b``` cant be negative and ```m``` cant be 0
+    ModularPower,
+    /// ```base(x, b)``` needs an integer base ```b``` from 2 to 36
+    Base,
+}
+
+/// A version of a builtin function which works with every kind of number
+/// It returns None if the result cant be represented by the kind of its arguments, like ```sqrt(2)``` with fractions
+pub(crate) type NumberFunction = fn(&[Number], &Context) -> Option<Number>;
+
+/// A function which is always available in the calculator, like ```sqrt```
+pub(crate) struct BuiltinFunction {
+    pub name: &'static str,
+    pub arity: Arity,
+    pub units: UnitRule,
+    pub arguments: ArgumentRule,
+    /// The arguments are checked against ```arity``` before this is called
+    pub function: fn(&[f64]) -> f64,
+    /// The version used when an argument isnt a float, like in ```NumberMode::Decimal``` or ```NumberMode::Complex```
+    /// If this is None or it returns None, ```function``` is called with the arguments converted to floats
+    pub number_function: Option<NumberFunction>,
+}
+
+pub(crate) const BUILTIN_FUNCTIONS: &[BuiltinFunction] = &[
+    //Roots and exponentials
+    BuiltinFunction {
+        name: "sqrt",
+        arity: Arity::Exact(1),
+        units: UnitRule::Root(2),
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].sqrt(),
+        number_function: Some(|args, context| args[0].sqrt(context)),
+    },
+    BuiltinFunction {
+        name: "cbrt",
+        arity: Arity::Exact(1),
+        units: UnitRule::Root(3),
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].cbrt(),
+        number_function: Some(|args, context| args[0].cbrt(context)),
+    },
+    BuiltinFunction {
+        name: "exp",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].exp(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::exp)),
+    },
+    BuiltinFunction {
+        name: "ln",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].ln(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::ln)),
+    },
+    //log(x) is the base 10 logarithm, log(x, base) can be used for any other base
+    BuiltinFunction {
+        name: "log",
+        arity: Arity::Range(1, 2),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        //The dedicated methods are more precise than the general one: log(1000, 10) is exactly 3
+        function: |args| match args.get(1) {
+            Some(10.) | None => args[0].log10(),
+            Some(2.) => args[0].log2(),
+            Some(base) => args[0].log(*base),
+        },
+        number_function: Some(|args, context| match args.get(1).map(Number::to_f64) {
+            Some(10.) | None => args[0].map_complex(Complex64::log10),
+            Some(2.) => args[0].map_complex(Complex64::log2),
+            Some(_) => Some(
+                args[0]
+                    .map_complex(Complex64::ln)?
+                    .div(Number::Complex(args[1].to_complex().ln()), context),
+            ),
+        }),
+    },
+    BuiltinFunction {
+        name: "log2",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].log2(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::log2)),
+    },
+    BuiltinFunction {
+        name: "log10",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].log10(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::log10)),
+    },
+    //Trigonometry, angles are in radians
+    BuiltinFunction {
+        name: "sin",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].sin(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::sin)),
+    },
+    BuiltinFunction {
+        name: "cos",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].cos(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::cos)),
+    },
+    BuiltinFunction {
+        name: "tan",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].tan(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::tan)),
+    },
+    BuiltinFunction {
+        name: "asin",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].asin(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::asin)),
+    },
+    BuiltinFunction {
+        name: "acos",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].acos(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::acos)),
+    },
+    BuiltinFunction {
+        name: "atan",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].atan(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::atan)),
+    },
+    //atan2(y, x)
+    BuiltinFunction {
+        name: "atan2",
+        arity: Arity::Exact(2),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].atan2(args[1]),
+        number_function: None,
+    },
+    BuiltinFunction {
+        name: "sinh",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].sinh(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::sinh)),
+    },
+    BuiltinFunction {
+        name: "cosh",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].cosh(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::cosh)),
+    },
+    BuiltinFunction {
+        name: "tanh",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].tanh(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::tanh)),
+    },
+    BuiltinFunction {
+        name: "asinh",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].asinh(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::asinh)),
+    },
+    BuiltinFunction {
+        name: "acosh",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].acosh(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::acosh)),
+    },
+    BuiltinFunction {
+        name: "atanh",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].atanh(),
+        number_function: Some(|args, _| args[0].map_complex(Complex64::atanh)),
+    },
+    //Rounding and sign
+    BuiltinFunction {
+        name: "abs",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].abs(),
+        number_function: Some(|args, _| Some(args[0].abs())),
+    },
+    BuiltinFunction {
+        name: "sign",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| if args[0] == 0. { 0. } else { args[0].signum() },
+        number_function: Some(|args, _| Some(args[0].sign())),
+    },
+    BuiltinFunction {
+        name: "floor",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].floor(),
+        number_function: Some(|args, _| Some(args[0].round_to_integer(RoundingMode::Floor))),
+    },
+    BuiltinFunction {
+        name: "ceil",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].ceil(),
+        number_function: Some(|args, _| Some(args[0].round_to_integer(RoundingMode::Ceiling))),
+    },
+    BuiltinFunction {
+        name: "round",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].round(),
+        number_function: Some(|args, _| Some(args[0].round_to_integer(RoundingMode::HalfUp))),
+    },
+    BuiltinFunction {
+        name: "trunc",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0].trunc(),
+        number_function: Some(|args, _| Some(args[0].round_to_integer(RoundingMode::Down))),
+    },
+    BuiltinFunction {
+        name: "gamma",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| gamma(args[0]),
+        number_function: None,
+    },
+    //These only change the base the answer is displayed in when they are the outermost call: hex(255) is 0xFF
+    BuiltinFunction {
+        name: "hex",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0],
+        number_function: Some(|args, _| Some(args[0].clone())),
+    },
+    BuiltinFunction {
+        name: "oct",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0],
+        number_function: Some(|args, _| Some(args[0].clone())),
+    },
+    BuiltinFunction {
+        name: "bin",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0],
+        number_function: Some(|args, _| Some(args[0].clone())),
+    },
+    //base(x, b) displays x in base b
+    BuiltinFunction {
+        name: "base",
+        arity: Arity::Exact(2),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Base,
+        function: |args| args[0],
+        number_function: Some(|args, _| Some(args[0].clone())),
+    },
+    //Functions taking any number of arguments
+    BuiltinFunction {
+        name: "min",
+        arity: Arity::AtLeast(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Real,
+        function: |args| args.iter().copied().fold(f64::INFINITY, f64::min),
+        number_function: Some(|args, _| {
+            args.iter()
+                .cloned()
+                .reduce(|min, arg| if arg < min { arg } else { min })
+        }),
+    },
+    BuiltinFunction {
+        name: "max",
+        arity: Arity::AtLeast(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Real,
+        function: |args| args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        number_function: Some(|args, _| {
+            args.iter()
+                .cloned()
+                .reduce(|max, arg| if arg > max { arg } else { max })
+        }),
+    },
+    BuiltinFunction {
+        name: "hypot",
+        arity: Arity::AtLeast(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args.iter().map(|arg| arg * arg).sum::<f64>().sqrt(),
+        number_function: None,
+    },
+    //Complex numbers, these also work with real numbers
+    BuiltinFunction {
+        name: "re",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0],
+        number_function: Some(|args, _| Some(args[0].real_part())),
+    },
+    BuiltinFunction {
+        name: "im",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |_| 0.,
+        number_function: Some(|args, _| Some(args[0].imaginary_part())),
+    },
+    //The angle between the number and the positive real axis
+    BuiltinFunction {
+        name: "arg",
+        arity: Arity::Exact(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Any,
+        function: |args| 0f64.atan2(args[0]),
+        number_function: Some(|args, _| args[0].map_complex(|value| Complex64::from(value.arg()))),
+    },
+    BuiltinFunction {
+        name: "conj",
+        arity: Arity::Exact(1),
+        units: UnitRule::Same,
+        arguments: ArgumentRule::Any,
+        function: |args| args[0],
+        number_function: Some(|args, _| {
+            Some(
+                args[0]
+                    .map_complex(|value| value.conj())
+                    .unwrap_or_else(|| args[0].clone()),
+            )
+        }),
+    },
+    //Number theory, these only work with integers
+    BuiltinFunction {
+        name: "gcd",
+        arity: Arity::AtLeast(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Integers,
+        function: |args| args.iter().copied().reduce(gcd).unwrap(),
+        number_function: Some(|args, _| {
+            integer_arguments(args)?
+                .into_iter()
+                .reduce(|gcd, integer| gcd.gcd(&integer))
+                .map(Number::Integer)
+        }),
+    },
+    BuiltinFunction {
+        name: "lcm",
+        arity: Arity::AtLeast(1),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::Integers,
+        function: |args| args.iter().copied().reduce(lcm).unwrap(),
+        number_function: Some(|args, _| {
+            integer_arguments(args)?
+                .into_iter()
+                .reduce(|lcm, integer| lcm.lcm(&integer))
+                .map(Number::Integer)
+        }),
+    },
+    //powmod(a, b, m) is a^b mod m, without calculating the huge power first
+    BuiltinFunction {
+        name: "powmod",
+        arity: Arity::Exact(3),
+        units: UnitRule::Dimensionless,
+        arguments: ArgumentRule::ModularPower,
+        function: |args| {
+            args.iter()
+                .map(|arg| {
+                    (arg.fract() == 0.)
+                        .then(|| BigInt::from_f64(*arg))
+                        .flatten()
+                })
+                .collect::<Option<Vec<BigInt>>>()
+                .and_then(|args| modular_power(&args[0], &args[1], &args[2]))
+                .and_then(|result| result.to_f64())
+                .unwrap_or(f64::NAN)
+        },
+        number_function: Some(|args, _| {
+            let args = integer_arguments(args)?;
+
+            modular_power(&args[0], &args[1], &args[2]).map(Number::Integer)
+        }),
+    },
+    //Dates, days_between(a, b) is negative if b is before a
+    BuiltinFunction {
+        name: "days_between",
+        arity: Arity::Exact(2),
+        units: UnitRule::Dates,
+        arguments: ArgumentRule::Any,
+        function: |args| args[1] - args[0],
+        number_function: Some(|args, context| Some(args[1].clone().sub(args[0].clone(), context))),
+    },
+];
+
+/// The arguments as integers of any size, None if one of them isnt an exact integer
+fn integer_arguments(args: &[Number]) -> Option<Vec<BigInt>> {
+    args.iter().map(Number::to_bigint).collect()
+}
+
+/// The greatest common divisor of two integers, the arguments are checked to be integers before this is called
+fn gcd(a: f64, b: f64) -> f64 {
+    if a.fract() != 0. || b.fract() != 0. {
+        return f64::NAN;
+    }
+
+    let (mut a, mut b) = (a.abs(), b.abs());
+
+    while b != 0. {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// The least common multiple of two integers
+fn lcm(a: f64, b: f64) -> f64 {
+    let divisor = gcd(a, b);
+
+    //The divisor is only 0 if both numbers are
+    if divisor == 0. {
+        return 0.;
+    }
+
+    (a / divisor * b).abs()
+}
+
+/// ```base^exponent mod modulus```, None if the exponent is negative or the modulus is 0 although the arguments are checked for this before
+fn modular_power(base: &BigInt, exponent: &BigInt, modulus: &BigInt) -> Option<BigInt> {
+    (!exponent.is_negative() && !modulus.is_zero()).then(|| base.modpow(exponent, modulus))
+}
+
+/// The coefficients of the Lanczos approximation with g = 7
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// The gamma function, ```gamma(n + 1) = n!```
+pub(crate) fn gamma(x: f64) -> f64 {
+    //The gamma function has poles at 0 and the negative integers
+    if x <= 0. && x.fract() == 0. {
+        return f64::NAN;
+    }
+
+    //Use the reflection formula for the left half, the approximation is only accurate for x >= 0.5
+    if x < 0.5 {
+        return std::f64::consts::PI / ((std::f64::consts::PI * x).sin() * gamma(1. - x));
+    }
+
+    let x = x - 1.;
+
+    let t = x + 7.5;
+
+    let series = LANCZOS_COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS_COEFFICIENTS[0], |sum, (index, coefficient)| {
+            sum + coefficient / (x + index as f64 + 1.)
+        });
+
+    (2. * std::f64::consts::PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * series
+}
+
+/// ```n!```, integers are multiplied out so the result is exact as long as it fits into a f64, anything else uses the gamma function
+/// Returns NaN for the negative integers and infinity if the result is too large
+pub(crate) fn factorial(n: f64) -> f64 {
+    if n.fract() == 0. && n >= 0. {
+        //171! doesnt fit into a f64 anymore
+        if n > 170. {
+            return f64::INFINITY;
+        }
+
+        return (2..=n as u64).map(|factor| factor as f64).product();
+    }
+
+    gamma(n + 1.)
+}
+
+/// ```n!! = n * (n - 2) * (n - 4) ...```, this is only defined for integers >= -1
+/// Returns NaN for any other value and infinity if the result is too large
+pub(crate) fn double_factorial(n: f64) -> f64 {
+    if n.fract() != 0. || n < -1. {
+        return f64::NAN;
+    }
+
+    let mut result: f64 = 1.;
+
+    let mut factor = n;
+
+    while factor > 1. {
+        result *= factor;
+
+        if result.is_infinite() {
+            break;
+        }
+
+        factor -= 2.;
+    }
+
+    result
+}
+
+/// Look up a builtin function by its name
+pub(crate) fn builtin_function(name: &str) -> Option<&'static BuiltinFunction> {
+    BUILTIN_FUNCTIONS
+        .iter()
+        .find(|function| function.name == name)
+}
+
+/// A function defined by the user with ```f(x, y) = x^2 + y```
+#[derive(Debug, Clone)]
+pub(crate) struct UserFunction {
+    pub parameters: Vec<String>,
+    pub body: Node,
+    /// The input the function was defined in, errors inside the body point into this
+    pub source: String,
+}
@@ -1,623 +1,607 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display, num::NonZeroU64, ops::Range, rc::Rc};
 
-use anyhow::{bail, Error, Result};
+use bigdecimal::Context;
 use thiserror::Error;
 
+mod constants;
+mod currency;
+mod diagnostics;
+mod evaluator;
+mod format;
+mod functions;
+mod number;
+mod parser;
+mod syntax;
+mod tokenizer;
+mod units;
+
+use constants::constant;
+use format::{format_clock, format_date, format_in_base, format_integer_in_base};
+use functions::{builtin_function, UserFunction};
+use parser::{parse, Node, NodeKind};
+use tokenizer::{tokenize, Span};
+use units::{Quantity, UnitKind};
+
+pub use bigdecimal::{BigDecimal, RoundingMode};
+pub use currency::{ExchangeRateError, ExchangeRates};
+pub use diagnostics::{Diagnostic, Label, Suggestion};
+pub use functions::Arity;
+pub use num_bigint::BigInt;
+pub use num_complex::Complex64;
+pub use num_rational::BigRational;
+pub use number::{Number, NumberMode};
+pub use syntax::{RecoveredParse, SyntaxKind, SyntaxNode};
+pub use units::Unit;
+
+/// The result of everything which can fail in a calculation
+type Result<T, E = CalculatorError> = std::result::Result<T, E>;
+
+/// The number of significant digits of ```NumberMode::Decimal``` if it isnt changed with ```Calculator::set_decimal_precision```
+const DEFAULT_DECIMAL_PRECISION: u64 = 28;
+
+pub struct Calculator {
+    /// Variables set with ```x = 3```, these are kept between calculations
+    variables: HashMap<String, Quantity>,
+    /// Functions defined with ```f(x) = x^2```, these are kept between calculations
+    functions: HashMap<String, Rc<UserFunction>>,
+    /// The arguments of the user defined functions currently being called, the last one is the innermost call
+    call_stack: Vec<HashMap<String, Quantity>>,
+    /// The summed depth of the bodies of the functions currently being called
+    body_depth: usize,
+    /// How ```%``` handles negative numbers
+    remainder_mode: RemainderMode,
+    /// Whether ```%``` is the remainder or a percentage
+    percent_mode: PercentMode,
+    /// The base the answers are displayed in
+    display_base: u32,
+    /// The base of the last ```base(x, b)``` call, the answer is displayed in it when the call is the outermost one
+    called_base: Option<u32>,
+    /// Which kind of numbers are used for the calculations
+    number_mode: NumberMode,
+    /// The precision and rounding of ```NumberMode::Decimal```
+    decimal_context: Context,
+    /// The rates currencies like ```USD``` are converted with
+    exchange_rates: ExchangeRates,
+    /// The warnings of the current calculation
+    warnings: Vec<Warning>,
+    /// Whether warnings are errors
+    strict: bool,
+}
+
+/// What ```%``` means
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PercentMode {
+    /// ```10 % 3``` is the remainder of the division
+    #[default]
+    Modulo,
+    /// ```%``` is a postfix percentage like on a desk calculator:
+    /// ```200 + 10%``` is 220, ```200 - 10%``` is 180, ```200 * 10%``` is 20 and ```50% of 80``` is 40
+    Percentage,
+}
+
+/// The sign of the remainder with ```%```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RemainderMode {
+    /// The remainder has the sign of the dividend: ```-7 % 3 = -1```
+    #[default]
+    Truncated,
+    /// The remainder is never negative: ```-7 % 3 = 2```
+    Euclidean,
+}
+
+/// The result of ```Calculator::execute```
 #[derive(Debug, Clone, PartialEq)]
-enum Expression {
-    Addition,
-    Subtraction,
-    Multiplication,
-    Division,
-    Power,
-
-    ///This is only used for parsing the equation later
-    /// (
-    LeftBracket,
-    /// )
-    RightBracket,
-
-    Brackets(Vec<Expression>),
-
-    Number(f64),
+pub enum Answer {
+    /// The base is only used when the number is displayed
+    Number {
+        value: Number,
+        /// The unit of the value, this is empty for plain numbers
+        unit: Unit,
+        base: u32,
+        warnings: Vec<Warning>,
+    },
+    /// A function was defined, this contains its name
+    FunctionDefinition(String),
 }
 
-impl Display for Expression {
+impl Display for Answer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&match self {
-            Expression::Addition => "+".to_string(),
-            Expression::Subtraction => "-".to_string(),
-            Expression::Multiplication => "*".to_string(),
-            Expression::Division => "/".to_string(),
-            Expression::Power => "^".to_string(),
-            Expression::LeftBracket => "(".to_string(),
-            Expression::RightBracket => ")".to_string(),
-            Expression::Brackets(inner_eq) => {
-                let inner_eq_str = inner_eq
-                    .iter()
-                    .map(|item| item.to_string())
-                    .collect::<Vec<String>>()
-                    .concat();
-
-                format!("({inner_eq_str})")
+        match self {
+            Answer::Number {
+                value, unit, base, ..
+            } => {
+                //Dates and times of the day are shown like they were written
+                match unit.kind() {
+                    UnitKind::Date => {
+                        if let Some(date) = format_date(value.to_f64()) {
+                            return f.write_str(&date);
+                        }
+                    }
+                    UnitKind::Clock => {
+                        if let Some(clock) = format_clock(value.to_f64()) {
+                            return f.write_str(&clock);
+                        }
+                    }
+                    UnitKind::Measure => {}
+                }
+
+                //Only other bases need the integer, it would have all the zeros of a decimal like 1e1000000
+                match base {
+                    10 => write!(f, "{value}")?,
+                    //Exact integers keep all of their digits
+                    _ => match value.to_bigint() {
+                        Some(integer) => f.write_str(&format_integer_in_base(&integer, *base))?,
+                        None => f.write_str(&format_in_base(value.to_f64(), *base))?,
+                    },
+                }
+
+                if !unit.is_empty() {
+                    write!(f, " {unit}")?;
+                }
+
+                Ok(())
             }
-            Expression::Number(inner_num) => format!("{}", inner_num),
-        })
+            Answer::FunctionDefinition(name) => write!(f, "Defined `{name}`"),
+        }
+    }
+}
+
+impl Answer {
+    /// The warnings of the calculation, definitions never have any
+    pub fn warnings(&self) -> &[Warning] {
+        match self {
+            Answer::Number { warnings, .. } => warnings,
+            Answer::FunctionDefinition(_) => &[],
+        }
     }
 }
 
-pub struct Calculator {}
+/// Something which makes the answer less reliable, unlike an error the answer is still calculated
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// A part of the input has no exact result in the number mode of the calculator, so it was calculated with floats
+    /// This contains that part of the input, like ```sqrt(2)``` or ```pi```
+    FloatFallback(String),
+    /// A part of the input has no real result, like ```(-1)^0.5``` or ```inf - inf```, so it is NaN
+    DomainError(String),
+    /// A part of the input is infinite although nothing it was calculated from is, like ```0^-1``` or ```ln(0)```
+    Infinity(String),
+    /// A part of the input is too large for a float, like ```10^400```, so it has become infinite
+    Overflow(String),
+    /// Adding or subtracting floats has cancelled most of their digits, like ```0.1 + 0.2 - 0.3```, so the result is mostly rounding errors
+    PrecisionLoss {
+        expression: String,
+        /// How many significant digits were lost, a float only has about 16
+        lost_digits: u32,
+    },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::FloatFallback(expression) => write!(
+                f,
+                "`{expression}` cant be represented exactly, the answer was calculated with floats"
+            ),
+            Warning::DomainError(expression) => {
+                write!(f, "`{expression}` isnt defined, it is NaN")
+            }
+            Warning::Infinity(expression) => write!(f, "`{expression}` is infinite"),
+            Warning::Overflow(expression) => write!(
+                f,
+                "`{expression}` is too large to be represented, it has become infinite"
+            ),
+            Warning::PrecisionLoss {
+                expression,
+                lost_digits,
+            } => write!(
+                f,
+                "`{expression}` has lost {lost_digits} significant digits, most of its digits are rounding errors"
+            ),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Error)]
 pub struct CalculatorError {
-    /// Error type
+    /// What went wrong
     err_type: CalculatorErrorType,
-    /// The index where the error occured
+    /// The bytes of the input which caused the error
     /// This is used for displaying the error
-    index: usize,
-    /// The erroring input ```experimental```
-    input: Vec<Expression>,
+    span: Span,
+    /// The erroring input
+    input: String,
 }
 
 impl Display for CalculatorError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&format!(
-            "Error type: {}, Index: {}",
-            self.err_type, self.index
+            "Error type: {}, Span: {}..{}",
+            self.err_type, self.span.start, self.span.end
         ))
     }
 }
 
+/// The error of an invalid setting, the setting is the input so it is pointed at when the error is shown
+fn setting_error(error_type: CalculatorErrorType, setting: impl Display) -> CalculatorError {
+    let input = setting.to_string();
+
+    CalculatorError::new(error_type, Span::new(0, input.len()), input)
+}
+
 impl CalculatorError {
-    fn new(error_type: CalculatorErrorType, index: usize, input: Vec<Expression>) -> Self {
+    fn new(error_type: CalculatorErrorType, span: Span, input: String) -> Self {
         Self {
             err_type: error_type,
-            index,
+            span,
             input,
         }
     }
 
-    pub fn show_error(&self) {
-        //Convert self.input to String
-        let erroring_input: String = self.input.iter().map(|item| item.to_string()).collect();
-
-        //Print out the user input equation
-        println!("[Error occured]\nEquation: \n{erroring_input}");
-
-        for _ in 0..self.index {
-            print!(" ")
-        }
+    /// What went wrong, this can be matched on to handle specific errors
+    pub fn error_type(&self) -> &CalculatorErrorType {
+        &self.err_type
+    }
 
-        println!("^\nError: {}", self.err_type);
-        println!("[Arrow may be at the wrong place]");
+    /// The byte range of the input which caused the error, ```&input[error.span()]``` is the offending text
+    /// The range is empty if something is missing at the end of the input, like in ```2 +```
+    pub fn span(&self) -> Range<usize> {
+        self.span.range()
     }
-}
 
-#[derive(Error, Debug, Clone)]
-enum CalculatorErrorType {
-    #[error("Error while trying to tokenize the input")]
-    ParseError,
+    /// The input the error was found in, for errors inside a user defined function this is its definition
+    pub fn input(&self) -> &str {
+        &self.input
+    }
 
-    ///Specific error codes are wrapped in this enum
-    #[error("This equation contains a conceptual error")]
-    /*
-        0: Tried to divide with 0
-    */
-    CalculationError(u8),
+    pub fn show_error(&self) {
+        //The input can end with the newline of the terminal
+        println!("[Error occured]\nEquation: \n{}", self.input.trim_end());
 
-    #[error("The equation contains invalid formatting, for example brackets left open")]
-    SyntaxError,
-}
+        //The carets are placed by characters, not bytes
+        let offset = self.input[..self.span.start].chars().count();
+        let width = self.input[self.span.range()].chars().count().max(1);
 
-impl Calculator {
-    pub fn new() -> Self {
-        Self {}
+        println!("{}{}", " ".repeat(offset), "^".repeat(width));
+        println!("Error: {}", self.err_type);
     }
+}
 
-    pub fn calculate(&mut self, input: &str) -> Result<f64> {
-        let formatted_calculation = input.trim().replace(" ", "");
-
-        Self::parse_equation(formatted_calculation.clone())
-    }
+/// What went wrong in a calculation, more kinds of errors may be added later
+#[derive(Error, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CalculatorErrorType {
+    #[error("The input contains a character which cant be calculated with")]
+    InvalidCharacter,
 
-    fn parse_equation(formatted_calculation: String) -> Result<f64> {
-        //Tokenize
-        let token_list = tokenize(formatted_calculation.clone())?;
+    #[error("The number couldnt be parsed, for example 1.2.3 or 25:00")]
+    InvalidNumberLiteral,
 
-        //Parse list, i.e introduce bracket items
-        let parsed_list = parse(token_list)?;
+    #[error("A bracket is never closed or has never been opened")]
+    UnbalancedBracket,
 
-        Self::calculate_equation(parsed_list)
-    }
+    #[error("This isnt expected here")]
+    UnexpectedToken,
 
-    fn calculate_equation(parsed_list: Vec<Expression>) -> Result<f64> {
-        let answ = calculate(parsed_list)?;
-
-        if let Expression::Number(number) = answ[0] {
-            return Ok(number);
-        } else {
-            bail!(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                0,
-                answ
-            ))
-        }
-    }
-}
+    #[error("The input has ended, but something is still missing")]
+    UnexpectedEndOfInput,
 
-fn tokenize(input: String) -> Result<Vec<Expression>> {
-    let mut final_list: Vec<Expression> = Vec::new();
+    #[error("Only units can be converted to, like `5 km in m`")]
+    InvalidConversion,
 
-    let mut number_buffer: String = String::new();
+    #[error("Tried to divide by 0")]
+    DivisionByZero,
 
-    for (index, char) in input.char_indices() {
-        //. means we are defining a float, self explnatory
-        if char.is_ascii_digit() || char == '.' {
-            //Push back char to the buffer
-            number_buffer.push(char);
-        }
-        //If its anything else then we need to push back the buffer, then we should clean it
-        //Then we should recognize what type of char is this (I think it would be better if we didnt panic if there was an invalid char)
-        //If number buffer is not empty (Contains a number, so it cant start with a non-nuumber character)
-        else if !number_buffer.is_empty() {
-            //Push back number to the final_list
-            match number_buffer.parse::<f64>() {
-                Ok(parsed_number) => {
-                    final_list.push(Expression::Number(parsed_number));
-                },
-                Err(_) => {
-                    bail!(CalculatorError::new(CalculatorErrorType::CalculationError(1), index, final_list));
-                },
-            }
+    #[error("Tried to take the remainder of a division by 0")]
+    RemainderByZero,
 
-            //Clear buffer
-            number_buffer.clear();
-        }
-        //If number_buffer is empty
-        if number_buffer.is_empty() {
-            //Recognize char if its an expression
-            final_list.push({
-                match char {
-                    '+' => Expression::Addition,
-                    '-' => Expression::Subtraction,
-                    '/' | '%' => Expression::Division,
-                    '*' => Expression::Multiplication,
-                    '^' => Expression::Power,
-                    ')' => Expression::RightBracket,
-                    '(' => Expression::LeftBracket,
-                    _ => {
-                        bail!(CalculatorError::new(
-                            CalculatorErrorType::ParseError,
-                            index,
-                            final_list /*This vector may be unfinished*/
-                        ))
-                    }
-                }
-            });
-        }
-    }
+    #[error("The result is too large to be represented")]
+    Overflow,
 
-    //If num buffer is not empty we should push it back, to save the last number
-    if !number_buffer.is_empty() {
-        match number_buffer.parse::<f64>() {
-            Ok(parsed_number) => {
-                final_list.push(Expression::Number(parsed_number));
-            },
-            Err(_) => {
-                bail!(CalculatorError::new(CalculatorErrorType::CalculationError(1), final_list.len() + 1, final_list));
-            },
-        }
-    }
+    #[error("The exact result would take too long to calculate, for example 1000000! with exact numbers")]
+    ExactResultTooLarge,
 
-    Ok(final_list)
-}
+    #[error("The operation isnt defined for this value, for example (-1)!")]
+    DomainError,
 
-/// Insert additional data for example () * <-- () and BracketItems
-fn parse(input: Vec<Expression>) -> Result<Vec<Expression>> {
-    //'Format' the input (We are just making out job easier down the road by inserting expressions)
-    let parsed_expression = parse_expressions(input)?;
+    #[error("The base has to be an integer between 2 and 36")]
+    InvalidBase,
 
-    let parsed_brackets = parse_brackets(parsed_expression)?;
+    #[error("The decimal precision has to be at least 1")]
+    InvalidPrecision,
 
-    Ok(parsed_brackets)
-}
+    #[error("Bitwise operators only work with integers")]
+    NonIntegerBitwiseOperand,
 
-fn parse_brackets(mut input: Vec<Expression>) -> Result<Vec<Expression>> {
-    //Check if there are any brackets
-    if !(input.contains(&Expression::LeftBracket) || input.contains(&Expression::RightBracket)) {
-        return Ok(input);
-    } 
+    #[error(
+        "A unit can only be raised to powers which keep its exponents integers, for example (2 m)^0.5 isnt possible"
+    )]
+    InvalidUnitPower,
 
-    let mut loop_index = 0;
+    #[error("Dates can only be moved by durations or subtracted from each other")]
+    InvalidDateOperation,
 
-    //This will count how deep are we in the brackets, which will become useful wehn we are dealing with nested brackets
-    let mut bracket_nest_level = 0;
+    #[error("There is no variable named `{0}`")]
+    UnknownVariable(String),
 
-    let mut first_left_bracket_occurence = 0;
+    #[error("There is no function named `{0}`")]
+    UnknownFunction(String),
 
-    //This buffer is used to capture the inner equation of a future bracket (its not yet inserted)
-    let mut eq_buffer: Vec<Expression> = Vec::new();
+    #[error("`{0}` is a function, its arguments have to be written in brackets")]
+    MissingArgumentBrackets(String),
 
-    while input.len() > loop_index {
-        let input_clone = input.clone();
+    #[error("Units with different dimensions cant be combined: {0} and {1}")]
+    DimensionMismatch(String, String),
 
-        //reset every temp* variable
-        eq_buffer.clear();
+    #[error("There is no exchange rate for `{0}`")]
+    MissingExchangeRate(String),
 
-        'nestedloop: for (index, item) in input_clone.iter().enumerate() {
-            //We check bracket nestedness if its 0 we can modify the input replaing a certain range with a new Bracket item
-            match item {
-                Expression::LeftBracket => {
-                    //if bracket nestedness is 0 that means that this left bracket is the beginning of a new inner equation
-                    if bracket_nest_level == 0 {
-                        //Set first* occurence
-                        first_left_bracket_occurence = index;
+    #[error("`{0}` is a constant, it cant be assigned to")]
+    ConstantAssignment(String),
 
-                        //We can only increment this variable for obvious reasons
-                        bracket_nest_level += 1;
+    #[error("`{name}` takes {expected} argument(s), but {found} were given")]
+    ArgumentCount {
+        name: String,
+        expected: Arity,
+        found: usize,
+    },
 
-                        //We should clear the buffer if we are starting to capture a bracket
-                        eq_buffer.clear();
+    #[error("The parameter `{0}` is written more than once")]
+    DuplicateParameter(String),
 
-                        //continue the nestedloop
-                        continue 'nestedloop;
-                    }
+    #[error("`{0}` is an operator, it cant be used as a name")]
+    ReservedName(String),
 
-                    bracket_nest_level += 1;
-                }
-                Expression::RightBracket => {
-                    bracket_nest_level -= 1;
-                }
-                Expression::Brackets(_) => {
-                    continue;
-                }
-                _ => {}
-            }
+    #[error("`{0}` is a builtin function, it cant be redefined")]
+    FunctionRedefinition(String),
 
-            if *item == Expression::RightBracket {
-                if bracket_nest_level == 0 {
-                    let bracket_item = Expression::Brackets(parse_brackets(eq_buffer.clone())?);
+    #[error(
+        "Function calls were nested deeper than {0}, the function may be calling itself endlessly"
+    )]
+    RecursionLimit(usize),
 
-                    input.drain(first_left_bracket_occurence..=index);
+    #[error("The calculation is nested deeper than {0} levels")]
+    NestingLimit(usize),
 
-                    input.insert(first_left_bracket_occurence, bracket_item);
+    /// A warning is an error when the calculator is strict, see ```Calculator::set_strict```
+    #[error("{0}")]
+    Warning(Warning),
 
-                    //Reset loop index to avoid skipping items
-                    loop_index = 0;
+    #[error(
+        "Function definitions dont have a value, use `Calculator::execute` to define functions"
+    )]
+    DefinitionNotAllowed,
+}
 
-                    //Break out of loop to work with the updated list
-                    break 'nestedloop;
-                }
-            }
+impl Default for Calculator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            //Push back item to buffer
-            eq_buffer.push(item.clone());
+impl Calculator {
+    pub fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+            call_stack: Vec::new(),
+            body_depth: 0,
+            remainder_mode: RemainderMode::default(),
+            percent_mode: PercentMode::default(),
+            display_base: 10,
+            called_base: None,
+            number_mode: NumberMode::default(),
+            decimal_context: Context::new(
+                NonZeroU64::new(DEFAULT_DECIMAL_PRECISION).unwrap(),
+                RoundingMode::HalfEven,
+            ),
+            exchange_rates: ExchangeRates::default(),
+            warnings: Vec::new(),
+            strict: false,
         }
+    }
 
-        //check for left open brackets
-        if bracket_nest_level != 0 {
-            bail!(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                dbg!(first_left_bracket_occurence),
-                input
-            ))
+    /// Set the base the answers of ```execute``` are displayed in, this can be overridden with ```hex(x)```, ```oct(x)```, ```bin(x)``` and ```base(x, b)```
+    /// The base has to be between 2 and 36, otherwise the error is ```CalculatorErrorType::InvalidBase```
+    pub fn set_display_base(&mut self, base: u32) -> Result<()> {
+        if !(2..=36).contains(&base) {
+            return Err(setting_error(CalculatorErrorType::InvalidBase, base));
         }
 
-        loop_index += 1;
-    }
-
-    Ok(input)
-}
+        self.display_base = base;
 
-fn parse_expressions(mut input: Vec<Expression>) -> Result<Vec<Expression>> {
-    //Last Expression we have iter-ed on
-    let mut last_expression: Option<Expression> = None;
-
-    let mut index = 0;
-
-    //We need to use a while loop in order to use a vector which is always updated (we shouldnt clone is the point)
-    while index < input.len() {
-        //This will only get ran when the index is 1 (Because we need to set the last expr. in the first iteration)
-        //We should borrow as mutable so we can modify the variable without putting Some() everywhere
-        if let Some(last_expression) = last_expression.as_mut() {
-            match &input[index] {
-                Expression::LeftBracket => {
-                    //This means if )( is true then we will insert a * between the two
-                    if *last_expression == Expression::RightBracket {
-                        input.insert(index, Expression::Multiplication);
-                    }
+        Ok(())
+    }
 
-                    //If last item was a number and this one is a ```(``` we insert a * on the same lgoic as in the Number match
-                    if matches!(*last_expression, Expression::Number(_)) {
-                        input.insert(index, Expression::Multiplication);
-                    }
-                }
-                Expression::RightBracket => {
-                    if *last_expression == Expression::LeftBracket {
-                        //Insert one index infront of us -> `)` * `(`
-                        input.insert(index + 1, Expression::Multiplication);
-                    }
-                }
-                Expression::Number(_) => {
-                    //If the last_expression was a number we need to add a * for the caluclator to multiply it later (and not crash)
-                    if *last_expression == Expression::RightBracket {
-                        input.insert(index, Expression::Multiplication);
-                    }
-                }
+    /// Set which kind of numbers are used for the calculations
+    pub fn set_number_mode(&mut self, number_mode: NumberMode) {
+        self.number_mode = number_mode;
+    }
 
-                _ => {}
-            }
+    /// Set how many significant digits the results have in ```NumberMode::Decimal```, the default is 28
+    /// A precision of 0 is rejected with ```CalculatorErrorType::InvalidPrecision```
+    pub fn set_decimal_precision(&mut self, precision: u64) -> Result<()> {
+        let Some(precision) = NonZeroU64::new(precision) else {
+            return Err(setting_error(
+                CalculatorErrorType::InvalidPrecision,
+                precision,
+            ));
+        };
 
-            //Save the last expression
-            *last_expression = input[index].clone();
-        } else {
-            //If there hasnt been any previous expressions then we can set the first one
-            last_expression = Some(input[index].clone());
-        }
+        self.decimal_context = self.decimal_context.with_precision(precision);
 
-        index += 1;
+        Ok(())
     }
 
-    Ok(input)
-}
+    /// Set how results with too many digits are rounded in ```NumberMode::Decimal```, the default is ```RoundingMode::HalfEven```
+    pub fn set_decimal_rounding(&mut self, rounding: RoundingMode) {
+        self.decimal_context = self.decimal_context.with_rounding_mode(rounding);
+    }
 
-fn calculate(mut input: Vec<Expression>) -> Result<Vec<Expression>> {
-    //Calculate with the right order of mathematical calculations
-    let mut loop_index;
-
-    //First check for ^
-    //Reset index
-    loop_index = 1;
-
-    while loop_index < input.len() {
-        
-        if input[loop_index] == Expression::Power {
-            let lhs = match input.get(loop_index - 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+    /// Set the exchange rates of currencies like ```120 USD + 80 EUR in GBP```, these can be loaded from a CSV or JSON file with ```ExchangeRates::from_file```
+    pub fn set_exchange_rates(&mut self, exchange_rates: ExchangeRates) {
+        self.exchange_rates = exchange_rates;
+    }
 
-            let rhs = match input.get(loop_index + 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+    /// Set whether ```%``` is the remainder or a percentage
+    pub fn set_percent_mode(&mut self, percent_mode: PercentMode) {
+        self.percent_mode = percent_mode;
+    }
 
-            let calc_result = lhs.powf(rhs);
+    /// Set whether warnings like a NaN result or a loss of precision are errors instead, the error type is ```CalculatorErrorType::Warning```
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
 
-            //Drain calculated parts of the equation
-            input.drain(loop_index - 1..=loop_index + 1);
+    /// Set how ```%``` handles negative numbers
+    pub fn set_remainder_mode(&mut self, remainder_mode: RemainderMode) {
+        self.remainder_mode = remainder_mode;
+    }
 
-            //Insert answ
-            input.insert(loop_index - 1, Expression::Number(calc_result));
-            
-            //Reset loop index
-            loop_index = 1;
-            //Break exceuction of this cycle
-            continue;
+    /// Calculate the value of an expression or an assignment like ```x = 3```
+    /// The result is converted to a float, use ```execute``` to get the exact result of ```NumberMode::Decimal```
+    /// The unit of the result is left out, ```5 km in m``` is 5000
+    pub fn calculate(&mut self, input: &str) -> Result<f64> {
+        match self.run(input, false)? {
+            Answer::Number { value, .. } => Ok(value.to_f64()),
+            //Definitions are rejected before they are stored
+            Answer::FunctionDefinition(_) => unreachable!(),
         }
+    }
 
-        loop_index += 1;
+    /// Same as ```calculate```, but this also accepts function definitions like ```f(x, y) = x^2 + y```
+    pub fn execute(&mut self, input: &str) -> Result<Answer> {
+        self.run(input, true)
     }
 
-    //Check for * /
-    //Reset index
-    loop_index = 1;
-
-    while loop_index < input.len() {
-        if input[loop_index] == Expression::Multiplication {
-            let lhs = match input.get(loop_index - 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+    fn run(&mut self, input: &str, allow_definitions: bool) -> Result<Answer> {
+        self.warnings.clear();
+        self.called_base = None;
 
-            let rhs = match input.get(loop_index + 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+        //The input isnt trimmed, so the spans point into exactly what was given
+        let token_list = tokenize(input)?;
 
-            let calc_result = lhs * rhs;
-
-            //Drain calculated parts of the equation
-            input.drain(loop_index - 1..=loop_index + 1);
-
-            //Insert answ
-            input.insert(loop_index - 1, Expression::Number(calc_result));
-
-            //Reset loop index
-            loop_index = 1;
-            //Break exceuction of this cycle
-            continue;
-        } else if input[loop_index] == Expression::Division {
-            let lhs = match input.get(loop_index - 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+        //Parse list into an expression tree
+        let mut expression_tree = parse(token_list, input, self)?;
 
-            let rhs = match input.get(loop_index + 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
+        let span = expression_tree.span;
+
+        if let NodeKind::FunctionDefinition { .. } = expression_tree.kind {
+            let NodeKind::FunctionDefinition {
+                name,
+                parameters,
+                body,
+            } = expression_tree.take_kind()
+            else {
+                unreachable!("The kind has just been matched")
             };
 
-            if rhs == 0. {
-                bail!(CalculatorError::new(
-                    CalculatorErrorType::CalculationError(0),
-                    loop_index + 1,
-                    input
-                ))
+            if !allow_definitions {
+                return Err(CalculatorError::new(
+                    CalculatorErrorType::DefinitionNotAllowed,
+                    span,
+                    input.to_string(),
+                ));
             }
 
-            let calc_result = lhs / rhs;
-
-            //Drain calculated parts of the equation
-            input.drain(loop_index - 1..=loop_index + 1);
+            if builtin_function(&name).is_some() {
+                return Err(CalculatorError::new(
+                    CalculatorErrorType::FunctionRedefinition(name),
+                    span,
+                    input.to_string(),
+                ));
+            }
 
-            //Insert answ
-            input.insert(loop_index - 1, Expression::Number(calc_result));
+            self.functions.insert(
+                name.clone(),
+                Rc::new(UserFunction {
+                    parameters,
+                    body: *body,
+                    source: input.to_string(),
+                }),
+            );
 
-            //Reset loop index
-            loop_index = 1;
-            //Break exceuction of this cycle
-            continue;
+            return Ok(Answer::FunctionDefinition(name));
         }
-        loop_index += 1;
-    }
 
-    //Check for + -
-    //Reset index
-    loop_index = 1;
-
-    while loop_index < input.len() {
-        if input[loop_index] == Expression::Addition {
-            let lhs = match input.get(loop_index - 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+        let Quantity { number, unit } = self.evaluate(&expression_tree, input)?;
 
-            let rhs = match input.get(loop_index + 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+        let base = self.display_base(&expression_tree);
 
-            let calc_result = lhs + rhs;
-
-            //Drain calculated parts of the equation
-            input.drain(loop_index - 1..=loop_index + 1);
-
-            //Insert answ
-            input.insert(loop_index - 1, Expression::Number(calc_result));
-
-            //Reset loop index
-            loop_index = 1;
-            //Break exceuction of this cycle
-            continue;
-
-        } else if input[loop_index] == Expression::Subtraction {
-            let lhs = match input.get(loop_index - 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+        //Only real numbers can be written in other bases
+        if base != 10 && !number.is_real() {
+            return Err(CalculatorError::new(
+                CalculatorErrorType::DomainError,
+                expression_tree.span,
+                input.to_string(),
+            ));
+        }
 
-            let rhs = match input.get(loop_index + 1).ok_or(CalculatorError::new(
-                CalculatorErrorType::SyntaxError,
-                loop_index,
-                input.clone(),
-            ))? {
-                Expression::Brackets(inner_eq) => Calculator::calculate_equation(inner_eq.clone())?,
-                Expression::Number(num) => *num,
-
-                _ => bail!(CalculatorError::new(
-                    CalculatorErrorType::SyntaxError,
-                    loop_index,
-                    input.clone()
-                )),
-            };
+        Ok(Answer::Number {
+            value: number,
+            unit,
+            base,
+            warnings: std::mem::take(&mut self.warnings),
+        })
+    }
 
-            let calc_result = lhs - rhs;
+    /// The base the answer should be displayed in, ```hex(x)```, ```oct(x)```, ```bin(x)``` and ```base(x, b)``` change it when they are the outermost call
+    /// The outermost call is evaluated last, so the base of ```base(x, b)``` is the one its evaluation has left behind
+    fn display_base(&self, expression_tree: &Node) -> u32 {
+        let NodeKind::Call { name, .. } = &expression_tree.kind else {
+            return self.display_base;
+        };
+
+        match name.as_str() {
+            "hex" => 16,
+            "oct" => 8,
+            "bin" => 2,
+            "base" => self.called_base.unwrap_or(self.display_base),
+            _ => self.display_base,
+        }
+    }
 
-            //Drain calculated parts of the equation
-            input.drain(loop_index - 1..=loop_index + 1);
+    /// Whether ```name``` can be called like ```name(...)```
+    fn is_function(&self, name: &str) -> bool {
+        builtin_function(name).is_some() || self.functions.contains_key(name)
+    }
 
-            //Insert answ
-            input.insert(loop_index - 1, Expression::Number(calc_result));
+    /// Whether ```name``` is a unit like ```km``` or a currency, variables and constants with the same name hide the unit
+    fn is_unit(&self, name: &str) -> bool {
+        self.constant(name).is_none()
+            && !self.variables.contains_key(name)
+            && (Unit::parse(name).is_some() || self.exchange_rates.is_currency(name))
+    }
 
-            //Reset loop index
-            loop_index = 1;
-            //Break exceuction of this cycle
-            continue;
+    /// Look up the value of a function argument, a variable, a constant, a unit or a currency
+    fn lookup(&self, name: &str) -> Option<Quantity> {
+        //Only the arguments of the innermost call are visible
+        if let Some(value) = self
+            .call_stack
+            .last()
+            .and_then(|arguments| arguments.get(name))
+        {
+            return Some(value.clone());
         }
 
-        loop_index += 1;
+        //Variables cant have the name of a constant, except i which is only a constant in NumberMode::Complex
+        self.constant(name)
+            .map(Quantity::from)
+            .or_else(|| self.variables.get(name).cloned())
+            .or_else(|| {
+                Unit::parse(name)
+                    .or_else(|| self.exchange_rates.unit(name))
+                    .map(|unit| Quantity::new(Number::from_integer(1, self.number_mode), unit))
+            })
     }
 
-    Ok(input)
+    /// Look up a constant, ```i``` is the imaginary unit in ```NumberMode::Complex```
+    fn constant(&self, name: &str) -> Option<Number> {
+        if self.number_mode == NumberMode::Complex && name == "i" {
+            return Some(Number::Complex(Complex64::i()));
+        }
+
+        //Constants are irrational or measured, so they are always floats
+        constant(name).map(Number::Float)
+    }
 }
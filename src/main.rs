@@ -1,34 +1,75 @@
-/* !
- So instead of 
-2, MUL, 3, MUL 9 
- I Should do 
-(2,MUL,(3, MUL, 9))
+use calculator_recode::Answer;
+use calculator_recode::Calculator;
+use calculator_recode::ExchangeRates;
+use calculator_recode::NumberMode;
+use std::io;
 
-*/
+/// Change a setting of the calculator with a command like ```:mode decimal```, ```:precision 50```, ```:strict on``` or ```:rates rates.csv```
+fn run_command(calculator: &mut Calculator, command: &str) -> Result<(), String> {
+    let mut words = command.split_whitespace();
+
+    match (words.next(), words.next()) {
+        (Some("mode"), Some("float")) => calculator.set_number_mode(NumberMode::Float),
+        (Some("mode"), Some("decimal")) => calculator.set_number_mode(NumberMode::Decimal),
+        (Some("mode"), Some("rational")) => calculator.set_number_mode(NumberMode::Rational),
+        (Some("mode"), Some("integer")) => calculator.set_number_mode(NumberMode::Integer),
+        (Some("mode"), Some("complex")) => calculator.set_number_mode(NumberMode::Complex),
+        (Some("rates"), Some(path)) => match ExchangeRates::from_file(path) {
+            Ok(exchange_rates) => calculator.set_exchange_rates(exchange_rates),
+            Err(err) => return Err(err.to_string()),
+        },
+        (Some("strict"), Some("on")) => calculator.set_strict(true),
+        (Some("strict"), Some("off")) => calculator.set_strict(false),
+        (Some("precision"), Some(precision)) => match precision.parse::<u64>() {
+            Ok(precision) => {
+                if let Err(err) = calculator.set_decimal_precision(precision) {
+                    return Err(err.error_type().to_string());
+                }
+            }
+            Err(_) => return Err(format!("`{precision}` isnt a valid precision")),
+        },
+        _ => return Err(format!("Unknown command `:{command}`")),
+    }
+
+    Ok(())
+}
 
-use std::io;
-use calculator_recode::Calculator;
-use calculator_recode::CalculatorError;
 fn main() {
     let mut calculator = Calculator::new();
-    
+
     let mut input_buffer: String = String::new();
 
     loop {
         match io::stdin().read_line(&mut input_buffer) {
-            Ok(_) => {
-                match calculator.calculate(&input_buffer) {
-                    Ok(answ) => println!("Answer: {answ}"),
-                    Err(err) => {
-                        err.downcast::<CalculatorError>().unwrap().show_error();
-                    },
+            //Stdin has been closed
+            Ok(0) => break,
+            //Lines starting with : change the settings
+            Ok(_) if input_buffer.trim_start().starts_with(':') => {
+                if let Err(err) = run_command(&mut calculator, &input_buffer.trim()[1..]) {
+                    println!("{err}");
+                }
+            }
+            Ok(_) => match calculator.execute(&input_buffer) {
+                Ok(answ @ Answer::Number { .. }) => {
+                    println!("Answer: {answ}");
+
+                    for warning in answ.warnings() {
+                        println!("Warning: {warning}");
+                    }
+                }
+                Ok(definition) => println!("{definition}"),
+                Err(err) => {
+                    //Every problem which can be found is shown, not only the first one
+                    for diagnostic in calculator.diagnose(&err) {
+                        println!("{}", diagnostic.render(err.input()));
+                    }
                 }
             },
             Err(err) => {
                 println!("{err}")
-            },
+            }
         };
-        
+
         //Clear input buffer
         input_buffer.clear();
     }
@@ -0,0 +1,829 @@
+use std::{cmp::Ordering, fmt::Display, ops::Neg, str::FromStr};
+
+use bigdecimal::{BigDecimal, Context, RoundingMode};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+
+use crate::{CalculatorErrorType, RemainderMode};
+
+/// Factorials of larger numbers take too long to multiply out, so they are an error in the exact number modes
+const MAX_EXACT_FACTORIAL: u64 = 10_000;
+
+/// Exact powers with more bits than this use the float version which overflows, so ```2^(10^12)``` doesnt use up all the memory
+const MAX_EXACT_POWER_BITS: u64 = 1 << 24;
+
+/// Decimals with more zeros before or after their digits than this arent converted to exact fractions, so ```1e100000000``` doesnt use up all the memory
+const MAX_EXACT_DECIMAL_EXPONENT: u32 = 1_000_000;
+
+/// Decimal powers whose first digit would be further from the decimal point than this use the float version which overflows
+const MAX_DECIMAL_POWER_MAGNITUDE: u64 = 1 << 24;
+
+/// Decimal exponents are only recognized as fractions like ```1/3``` up to this odd denominator, so odd roots of negative decimals stay real
+const MAX_ODD_ROOT: u32 = 99;
+
+/// Decimals whose first digit is further from the decimal point than this are written in scientific notation
+const MAX_PLAIN_DECIMAL_MAGNITUDE: u64 = 100;
+
+/// Integers above this arent exact as floats anymore
+const MAX_EXACT_FLOAT_INTEGER: f64 = 9_007_199_254_740_992.;
+
+/// Shift an integer to the left, ```None``` if the result would have more bits than an exact power may have
+pub(crate) fn shift_left(value: &BigInt, shift: u64) -> Option<BigInt> {
+    (value.bits().saturating_add(shift) <= MAX_EXACT_POWER_BITS).then(|| value << shift)
+}
+
+/// Which kind of numbers the calculator evaluates with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberMode {
+    /// 64 bit floating point numbers, these are the fastest: ```0.1 + 0.2 = 0.30000000000000004```
+    #[default]
+    Float,
+    /// Exact decimal numbers for financial work: ```0.1 + 0.2 = 0.3```
+    /// Results with more significant digits than the decimal precision are rounded, like ```1 / 3```
+    Decimal,
+    /// Exact fractions: ```1/3 + 1/6 = 1/2```
+    /// Irrational results like ```sqrt(2)``` are calculated with floats
+    Rational,
+    /// Integers of any size: ```2^200``` and ```30!``` have all of their digits
+    /// Dividing integers which dont divide exactly like ```7/2``` is an error
+    Integer,
+    /// Complex numbers with float parts, ```i``` is the imaginary unit: ```sqrt(-4) = 2i```
+    Complex,
+}
+
+impl NumberMode {
+    /// Whether the numbers of this mode are exact, so falling back to floats has to be reported
+    pub(crate) fn is_exact(self) -> bool {
+        matches!(
+            self,
+            NumberMode::Decimal | NumberMode::Rational | NumberMode::Integer
+        )
+    }
+}
+
+/// A number the calculator has evaluated, its kind depends on the ```NumberMode``` of the calculator
+/// Operations which have no exact version, like ```sin(x)```, always result in a float
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Float(f64),
+    Decimal(BigDecimal),
+    Rational(BigRational),
+    Integer(BigInt),
+    Complex(Complex64),
+}
+
+/// A number literal from the input, it is stored in every form so it only has to be parsed once
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Literal {
+    pub float: f64,
+    /// The exact value of the literal, ```0.1``` cant be represented by a float exactly
+    pub exact: BigDecimal,
+}
+
+impl Literal {
+    /// Parse a decimal number like ```0.3048``` or ```1e-3```, None if it isnt valid
+    pub fn parse(text: &str) -> Option<Literal> {
+        text.parse::<f64>()
+            .ok()
+            .zip(BigDecimal::from_str(text).ok())
+            .map(|(float, exact)| Literal { float, exact })
+    }
+}
+
+/// Two numbers converted to the same kind, so an operation can be done on them
+enum Operands {
+    Float(f64, f64),
+    Decimal(BigDecimal, BigDecimal),
+    Rational(BigRational, BigRational),
+    Integer(BigInt, BigInt),
+    Complex(Complex64, Complex64),
+}
+
+/// Convert both numbers to the same kind, if one of them is a float the other one is converted to a float too
+fn operands(lhs: Number, rhs: Number) -> Operands {
+    match (lhs, rhs) {
+        //Anything can be converted to a complex number, but the exact kinds lose their precision
+        (lhs @ Number::Complex(_), rhs) | (lhs, rhs @ Number::Complex(_)) => {
+            Operands::Complex(lhs.to_complex(), rhs.to_complex())
+        }
+        (Number::Decimal(lhs), Number::Decimal(rhs)) => Operands::Decimal(lhs, rhs),
+        (Number::Rational(lhs), Number::Rational(rhs)) => Operands::Rational(lhs, rhs),
+        (Number::Integer(lhs), Number::Integer(rhs)) => Operands::Integer(lhs, rhs),
+        //Integers can be converted to decimals exactly, and both can be converted to fractions exactly, but not the other way around
+        (Number::Integer(lhs), Number::Decimal(rhs)) => Operands::Decimal(lhs.into(), rhs),
+        (Number::Decimal(lhs), Number::Integer(rhs)) => Operands::Decimal(lhs, rhs.into()),
+        (lhs, rhs) => match (lhs.to_rational(), rhs.to_rational()) {
+            (Some(lhs), Some(rhs)) => Operands::Rational(lhs, rhs),
+            _ => Operands::Float(lhs.to_f64(), rhs.to_f64()),
+        },
+    }
+}
+
+fn decimal_to_rational(value: &BigDecimal) -> Option<BigRational> {
+    let (digits, scale) = value.as_bigint_and_exponent();
+
+    let power = u32::try_from(scale.unsigned_abs())
+        .ok()
+        .filter(|power| *power <= MAX_EXACT_DECIMAL_EXPONENT)?;
+
+    //A negative scale means the digits are followed by zeros
+    Some(if scale >= 0 {
+        BigRational::new(digits, BigInt::from(10).pow(power))
+    } else {
+        BigRational::from_integer(digits * BigInt::from(10).pow(power))
+    })
+}
+
+/// The exact result of an operation on integers, its only a fraction if it has to be
+fn integer_or_fraction(value: BigRational) -> Number {
+    if value.is_integer() {
+        Number::Integer(value.to_integer())
+    } else {
+        Number::Rational(value)
+    }
+}
+
+/// The n-th root of a fraction if its a fraction too, like the square root of ```4/9```
+fn exact_root(value: &BigRational, n: u32) -> Option<BigRational> {
+    //Even roots of negative numbers arent real
+    if n.is_even() && value.is_negative() {
+        return None;
+    }
+
+    let root = |integer: &BigInt| {
+        let root = integer.nth_root(n);
+
+        (root.pow(n) == *integer).then_some(root)
+    };
+
+    Some(BigRational::new(root(value.numer())?, root(value.denom())?))
+}
+
+/// Raise a fraction to a fractional power if the result is a fraction too, ```x^(p/q)``` is the q-th root of x to the power of p
+fn exact_power(base: &BigRational, exponent: &BigRational) -> Option<BigRational> {
+    let root = exact_root(base, exponent.denom().to_u32()?)?;
+
+    let power = exponent.numer().to_i32()?;
+
+    //0 to a negative power would be a division by 0, the float version returns infinity
+    if root.is_zero() && power < 0 {
+        return None;
+    }
+
+    if root.numer().bits().max(root.denom().bits()) * power.unsigned_abs() as u64
+        > MAX_EXACT_POWER_BITS
+    {
+        return None;
+    }
+
+    Some(root.pow(power))
+}
+
+/// The fraction with an odd denominator a decimal exponent was rounded from, like ```1/3``` for ```0.3333333333333333333333333333```
+/// Decimals cant represent ```1/3```, but negative decimals still need its odd root to be real like in ```NumberMode::Rational```
+fn odd_root_exponent(exponent: &BigDecimal, context: &Context) -> Option<BigRational> {
+    (3..=MAX_ODD_ROOT).step_by(2).find_map(|denominator| {
+        let denominator = BigDecimal::from(denominator);
+
+        let numerator = (exponent * &denominator).with_scale_round(0, RoundingMode::HalfEven);
+
+        (divide(&numerator, &denominator, context) == *exponent).then(|| {
+            BigRational::new(
+                numerator.as_bigint_and_exponent().0,
+                denominator.as_bigint_and_exponent().0,
+            )
+        })
+    })
+}
+
+/// Raise a negative decimal to a fractional power with an odd denominator, the result is real like ```(-8)^(1/3) = -2```
+/// The result is exact if the root is, otherwise it is calculated with floats
+fn negative_decimal_power(base: &BigDecimal, exponent: &BigRational, context: &Context) -> Number {
+    if let Some(power) = decimal_to_rational(base).and_then(|base| exact_power(&base, exponent)) {
+        return Number::Decimal(divide(
+            &BigDecimal::from(power.numer().clone()),
+            &BigDecimal::from(power.denom().clone()),
+            context,
+        ));
+    }
+
+    let magnitude = Number::Decimal(base.abs())
+        .to_f64()
+        .powf(Number::Rational(exponent.clone()).to_f64());
+
+    //An odd numerator keeps the sign of the base
+    Number::Float(if exponent.numer().is_odd() {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+/// Raise a complex number to a complex power
+/// Integer powers are multiplied out and real powers of positive numbers stay real, so ```i^2``` is exactly -1
+fn complex_power(base: Complex64, exponent: Complex64) -> Complex64 {
+    if exponent.im == 0. && exponent.re.fract() == 0. && exponent.re.abs() <= i32::MAX as f64 {
+        let power = base.powi(exponent.re as i32);
+
+        //Multiplying out powers which overflow can subtract infinities from each other, the polar form keeps them infinite
+        if !power.is_nan() || !base.is_finite() {
+            return power;
+        }
+    }
+
+    if base.im == 0. && exponent.im == 0. && base.re >= 0. {
+        return base.re.powf(exponent.re).into();
+    }
+
+    base.powc(exponent)
+}
+
+/// Round a decimal to the precision of the context
+/// Unlike ```Context::round_decimal``` this doesnt pad shorter decimals with zeros,
+/// and the zeros left over by rounding like in ```2/3*3 = 2.000000000000000000000000000``` are removed
+fn round(value: BigDecimal, context: &Context) -> BigDecimal {
+    if value.digits() > context.precision().get() {
+        context.round_decimal(value).normalized()
+    } else {
+        value
+    }
+}
+
+/// Add two decimals, the sum is rounded to the precision of the context
+/// A decimal too small to change any digit of the other one only decides how the sum is rounded, it is replaced by a tiny decimal with the same sign
+/// Otherwise ```1e1000000 + 1``` would be added with a million digits, just to round them away again
+fn add(lhs: BigDecimal, rhs: BigDecimal, context: &Context) -> BigDecimal {
+    if lhs.is_zero() || rhs.is_zero() {
+        return round(lhs + rhs, context);
+    }
+
+    let (larger, smaller) = if lhs.order_of_magnitude() >= rhs.order_of_magnitude() {
+        (lhs, rhs)
+    } else {
+        (rhs, lhs)
+    };
+
+    //Every digit of the larger decimal and of the rounded sum is above this position, even if the sum has one digit less
+    let lowest_digit = (-larger.fractional_digit_count())
+        .min(larger.order_of_magnitude() - context.precision().get() as i64 - 1);
+
+    if smaller.order_of_magnitude() < lowest_digit {
+        let sign = if smaller.is_negative() { -1 } else { 1 };
+
+        round(
+            larger + BigDecimal::new(sign.into(), 1 - lowest_digit),
+            context,
+        )
+    } else {
+        round(larger + smaller, context)
+    }
+}
+
+/// Divide two decimals, the quotient is exact if it has few enough digits, otherwise its rounded to the precision of the context
+fn divide(lhs: &BigDecimal, rhs: &BigDecimal, context: &Context) -> BigDecimal {
+    let (lhs_digits, lhs_scale) = lhs.as_bigint_and_exponent();
+    let (rhs_digits, rhs_scale) = rhs.as_bigint_and_exponent();
+
+    //Shift the dividend so the quotient has at least two more digits than the precision
+    let shift = context.precision().get() + 2 + rhs.digits();
+
+    let (quotient, remainder) =
+        (lhs_digits * BigInt::from(10).pow(shift as u32)).div_rem(&rhs_digits);
+
+    let scale = lhs_scale - rhs_scale + shift as i64;
+
+    let quotient = if remainder.is_zero() {
+        BigDecimal::new(quotient, scale)
+    } else {
+        //Add one more digit which marks that the quotient continues, so its never rounded like an exact half
+        let sticky_digit = if quotient.is_negative() { -1 } else { 1 };
+
+        BigDecimal::new(quotient * 10 + sticky_digit, scale + 1)
+    };
+
+    round(quotient, context).normalized()
+}
+
+impl Number {
+    pub(crate) fn from_literal(literal: &Literal, mode: NumberMode) -> Self {
+        match mode {
+            NumberMode::Float => Number::Float(literal.float),
+            NumberMode::Decimal => Number::Decimal(literal.exact.clone()),
+            //Exponents too large for exact fractions, like 1e100000000, fall back to floats
+            NumberMode::Rational => decimal_to_rational(&literal.exact)
+                .map_or(Number::Float(literal.float), Number::Rational),
+            NumberMode::Integer => decimal_to_rational(&literal.exact)
+                .map_or(Number::Float(literal.float), integer_or_fraction),
+            NumberMode::Complex => Number::Complex(literal.float.into()),
+        }
+    }
+
+    /// The results of integer operations like ```5 & 3```
+    pub(crate) fn from_integer(value: i64, mode: NumberMode) -> Self {
+        match mode {
+            NumberMode::Float => Number::Float(value as f64),
+            NumberMode::Decimal => Number::Decimal(BigDecimal::from(value)),
+            NumberMode::Rational => Number::Rational(BigRational::from_integer(value.into())),
+            NumberMode::Integer => Number::Integer(value.into()),
+            NumberMode::Complex => Number::Complex((value as f64).into()),
+        }
+    }
+
+    /// Convert an integer of any size to the kind of number of the mode, this can lose precision in the float modes
+    pub(crate) fn from_bigint(value: BigInt, mode: NumberMode) -> Self {
+        match mode {
+            NumberMode::Float => Number::Float(value.to_f64().unwrap_or(f64::NAN)),
+            NumberMode::Decimal => Number::Decimal(BigDecimal::from(value)),
+            NumberMode::Rational => Number::Rational(BigRational::from_integer(value)),
+            NumberMode::Integer => Number::Integer(value),
+            NumberMode::Complex => Number::Complex(value.to_f64().unwrap_or(f64::NAN).into()),
+        }
+    }
+
+    /// Whether the number is a float, these never need a separate version of an operation
+    pub(crate) fn is_float(&self) -> bool {
+        matches!(self, Number::Float(_))
+    }
+
+    /// Convert the number to a fraction, which is exact for every kind of number except floats
+    fn to_rational(&self) -> Option<BigRational> {
+        match self {
+            Number::Float(_) | Number::Complex(_) => None,
+            Number::Decimal(value) => decimal_to_rational(value),
+            Number::Rational(value) => Some(value.clone()),
+            Number::Integer(value) => Some(BigRational::from_integer(value.clone())),
+        }
+    }
+
+    /// The number as an integer of any size if it is an exact integer
+    pub(crate) fn to_bigint(&self) -> Option<BigInt> {
+        match self {
+            Number::Integer(value) => Some(value.clone()),
+            number => number
+                .to_rational()
+                .filter(BigRational::is_integer)
+                .map(|value| value.to_integer()),
+        }
+    }
+
+    /// The number as an integer of any size for the bitwise operators, floats too unlike ```to_bigint```
+    /// Floats above 2^53 have lost their last bits, so these overflow instead of giving wrong bits
+    pub(crate) fn to_bitwise_integer(&self) -> Result<BigInt, CalculatorErrorType> {
+        match self {
+            Number::Float(_) | Number::Complex(_) => {
+                let value = self.to_f64();
+                if value.fract() != 0. || !value.is_finite() {
+                    Err(CalculatorErrorType::NonIntegerBitwiseOperand)
+                } else if value.abs() > MAX_EXACT_FLOAT_INTEGER {
+                    Err(CalculatorErrorType::Overflow)
+                } else {
+                    BigInt::from_f64(value).ok_or(CalculatorErrorType::Overflow)
+                }
+            }
+            number => number
+                .to_bigint()
+                .ok_or(CalculatorErrorType::NonIntegerBitwiseOperand),
+        }
+    }
+
+    /// Whether the number is an integer, floats too unlike ```to_bigint```
+    pub(crate) fn is_integer(&self) -> bool {
+        match self {
+            Number::Float(_) | Number::Complex(_) => {
+                let value = self.to_f64();
+
+                value.is_finite() && value.fract() == 0.
+            }
+            Number::Decimal(value) => value.is_integer(),
+            Number::Rational(value) => value.is_integer(),
+            Number::Integer(_) => true,
+        }
+    }
+
+    /// Whether the number has no imaginary part, every kind of number except complex numbers is real
+    pub(crate) fn is_real(&self) -> bool {
+        !matches!(self, Number::Complex(value) if value.im != 0.)
+    }
+
+    /// Whether the number is exact, floats and complex numbers arent
+    pub(crate) fn is_exact(&self) -> bool {
+        !matches!(self, Number::Float(_) | Number::Complex(_))
+    }
+
+    pub(crate) fn to_complex(&self) -> Complex64 {
+        match self {
+            Number::Complex(value) => *value,
+            number => number.to_f64().into(),
+        }
+    }
+
+    /// Convert the number to a float, this can lose precision
+    /// Complex numbers are only converted if they dont have an imaginary part, otherwise this is NaN
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Float(value) => *value,
+            //Parsing the digits rounds correctly, which converting the digits and the scale separately doesnt
+            Number::Decimal(value) => value.to_string().parse().unwrap_or(f64::NAN),
+            Number::Rational(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Integer(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Complex(value) if value.im == 0. => value.re,
+            Number::Complex(_) => f64::NAN,
+        }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        match self {
+            Number::Float(value) => *value == 0.,
+            Number::Decimal(value) => value.is_zero(),
+            Number::Rational(value) => value.is_zero(),
+            Number::Integer(value) => value.is_zero(),
+            Number::Complex(value) => value.is_zero(),
+        }
+    }
+
+    /// Only floats and complex numbers can be NaN
+    pub(crate) fn is_nan(&self) -> bool {
+        match self {
+            Number::Float(value) => value.is_nan(),
+            Number::Complex(value) => value.is_nan(),
+            _ => false,
+        }
+    }
+
+    /// Only floats and complex numbers can be infinite
+    pub(crate) fn is_infinite(&self) -> bool {
+        match self {
+            Number::Float(value) => value.is_infinite(),
+            Number::Complex(value) => value.is_infinite(),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn add(self, rhs: Number, context: &Context) -> Number {
+        match operands(self, rhs) {
+            Operands::Float(lhs, rhs) => Number::Float(lhs + rhs),
+            Operands::Decimal(lhs, rhs) => Number::Decimal(add(lhs, rhs, context)),
+            Operands::Rational(lhs, rhs) => Number::Rational(lhs + rhs),
+            Operands::Integer(lhs, rhs) => Number::Integer(lhs + rhs),
+            Operands::Complex(lhs, rhs) => Number::Complex(lhs + rhs),
+        }
+    }
+
+    pub(crate) fn sub(self, rhs: Number, context: &Context) -> Number {
+        match operands(self, rhs) {
+            Operands::Float(lhs, rhs) => Number::Float(lhs - rhs),
+            Operands::Decimal(lhs, rhs) => Number::Decimal(add(lhs, -rhs, context)),
+            Operands::Rational(lhs, rhs) => Number::Rational(lhs - rhs),
+            Operands::Integer(lhs, rhs) => Number::Integer(lhs - rhs),
+            Operands::Complex(lhs, rhs) => Number::Complex(lhs - rhs),
+        }
+    }
+
+    pub(crate) fn mul(self, rhs: Number, context: &Context) -> Number {
+        match operands(self, rhs) {
+            Operands::Float(lhs, rhs) => Number::Float(lhs * rhs),
+            Operands::Decimal(lhs, rhs) => Number::Decimal(round(lhs * rhs, context)),
+            Operands::Rational(lhs, rhs) => Number::Rational(lhs * rhs),
+            Operands::Integer(lhs, rhs) => Number::Integer(lhs * rhs),
+            Operands::Complex(lhs, rhs) => Number::Complex(lhs * rhs),
+        }
+    }
+
+    /// The divisor has to be checked for 0 before calling this
+    pub(crate) fn div(self, rhs: Number, context: &Context) -> Number {
+        match operands(self, rhs) {
+            Operands::Float(lhs, rhs) => Number::Float(lhs / rhs),
+            Operands::Decimal(lhs, rhs) => Number::Decimal(divide(&lhs, &rhs, context)),
+            Operands::Rational(lhs, rhs) => Number::Rational(lhs / rhs),
+            Operands::Integer(lhs, rhs) => integer_or_fraction(BigRational::new(lhs, rhs)),
+            Operands::Complex(lhs, rhs) => Number::Complex(lhs / rhs),
+        }
+    }
+
+    /// The remainder of a division, the divisor has to be checked for 0 before calling this
+    pub(crate) fn rem(self, rhs: Number, remainder_mode: RemainderMode) -> Number {
+        match operands(self, rhs) {
+            Operands::Float(lhs, rhs) => Number::Float(match remainder_mode {
+                RemainderMode::Truncated => lhs % rhs,
+                RemainderMode::Euclidean => lhs.rem_euclid(rhs),
+            }),
+            Operands::Decimal(lhs, rhs) => {
+                let remainder = &lhs % &rhs;
+
+                Number::Decimal(
+                    if remainder_mode == RemainderMode::Euclidean && remainder.is_negative() {
+                        remainder + rhs.abs()
+                    } else {
+                        remainder
+                    },
+                )
+            }
+            Operands::Rational(lhs, rhs) => {
+                let remainder = &lhs % &rhs;
+
+                Number::Rational(
+                    if remainder_mode == RemainderMode::Euclidean && remainder.is_negative() {
+                        remainder + rhs.abs()
+                    } else {
+                        remainder
+                    },
+                )
+            }
+            Operands::Integer(lhs, rhs) => {
+                let remainder = &lhs % &rhs;
+
+                Number::Integer(
+                    if remainder_mode == RemainderMode::Euclidean && remainder.is_negative() {
+                        remainder + rhs.abs()
+                    } else {
+                        remainder
+                    },
+                )
+            }
+            //The remainder is only defined for real numbers, the evaluator rejects the others
+            Operands::Complex(lhs, rhs) => Number::Complex(
+                Number::Float(Number::Complex(lhs).to_f64())
+                    .rem(Number::Float(Number::Complex(rhs).to_f64()), remainder_mode)
+                    .to_f64()
+                    .into(),
+            ),
+        }
+    }
+
+    /// Decimals can only be raised to integer powers exactly, fractions to any power with a fractional result like ```(8/27)^(2/3)```
+    /// Other powers use floats
+    pub(crate) fn pow(self, rhs: Number, context: &Context) -> Number {
+        match operands(self, rhs) {
+            Operands::Float(lhs, rhs) => Number::Float(lhs.powf(rhs)),
+            Operands::Decimal(lhs, rhs) => {
+                let exponent = rhs.is_integer().then(|| rhs.to_i64()).flatten();
+
+                //The magnitude of the power is at most the magnitude of the base times the exponent
+                let magnitude = exponent.map(|exponent| {
+                    (lhs.order_of_magnitude().unsigned_abs() + 1)
+                        .saturating_mul(exponent.unsigned_abs())
+                });
+
+                match exponent {
+                    //0 to a negative power would be a division by 0 and huge powers would have huge exponents
+                    //The float version returns infinity for both
+                    Some(exponent)
+                        if !(lhs.is_zero() && exponent < 0)
+                            && magnitude <= Some(MAX_DECIMAL_POWER_MAGNITUDE) =>
+                    {
+                        Number::Decimal(
+                            round(lhs.powi_with_context(exponent, context), context).normalized(),
+                        )
+                    }
+                    None if lhs.is_negative() => match odd_root_exponent(&rhs, context) {
+                        Some(exponent) => negative_decimal_power(&lhs, &exponent, context),
+                        None => Number::Float(
+                            Number::Decimal(lhs)
+                                .to_f64()
+                                .powf(Number::Decimal(rhs).to_f64()),
+                        ),
+                    },
+                    _ => Number::Float(
+                        Number::Decimal(lhs)
+                            .to_f64()
+                            .powf(Number::Decimal(rhs).to_f64()),
+                    ),
+                }
+            }
+            Operands::Rational(lhs, rhs) => match exact_power(&lhs, &rhs) {
+                Some(power) => Number::Rational(power),
+                None => Number::Float(
+                    Number::Rational(lhs)
+                        .to_f64()
+                        .powf(Number::Rational(rhs).to_f64()),
+                ),
+            },
+            Operands::Integer(lhs, rhs) => {
+                match exact_power(&lhs.clone().into(), &rhs.clone().into()) {
+                    Some(power) => integer_or_fraction(power),
+                    None => Number::Float(
+                        Number::Integer(lhs)
+                            .to_f64()
+                            .powf(Number::Integer(rhs).to_f64()),
+                    ),
+                }
+            }
+            Operands::Complex(lhs, rhs) => Number::Complex(complex_power(lhs, rhs)),
+        }
+    }
+
+    /// Multiply ```n * (n - step) * (n - 2 * step) ...``` out exactly, this returns None if the number is a float or it isnt a positive integer
+    /// The factorial uses a step of 1, the double factorial uses 2
+    /// Integers above ```MAX_EXACT_FACTORIAL``` are ```CalculatorErrorType::ExactResultTooLarge```, the float version would only overflow
+    pub(crate) fn exact_factorial(
+        &self,
+        step: usize,
+        context: &Context,
+    ) -> Result<Option<Number>, CalculatorErrorType> {
+        let n = match self {
+            Number::Float(_) | Number::Complex(_) => return Ok(None),
+            number => match number.to_bigint().filter(|n| !n.is_negative()) {
+                Some(n) => n.to_u64().unwrap_or(u64::MAX),
+                None => return Ok(None),
+            },
+        };
+
+        if n > MAX_EXACT_FACTORIAL {
+            return Err(CalculatorErrorType::ExactResultTooLarge);
+        }
+
+        let factors = (1..=n).rev().step_by(step);
+
+        Ok(Some(match self {
+            Number::Rational(_) => Number::Rational(BigRational::from_integer(
+                factors.map(BigInt::from).product(),
+            )),
+            Number::Integer(_) => Number::Integer(factors.map(BigInt::from).product()),
+            _ => Number::Decimal(factors.fold(BigDecimal::one(), |product, factor| {
+                round(product * BigDecimal::from(factor), context)
+            })),
+        }))
+    }
+
+    /// The square root of a decimal, a fraction or a complex number, None if the number is a float or the root cant be represented by its kind
+    pub(crate) fn sqrt(&self, context: &Context) -> Option<Number> {
+        match self {
+            Number::Float(_) => None,
+            Number::Decimal(value) => value
+                .sqrt_with_context(context)
+                .map(|root| Number::Decimal(root.normalized())),
+            Number::Rational(value) => exact_root(value, 2).map(Number::Rational),
+            Number::Integer(value) => exact_root(&value.clone().into(), 2).map(integer_or_fraction),
+            Number::Complex(value) => Some(Number::Complex(value.sqrt())),
+        }
+    }
+
+    /// The cube root of a decimal, a fraction or a complex number, None if the number is a float or the root cant be represented by its kind
+    /// The cube root of a complex number is the principal one, so ```cbrt(-8)``` is ```1 + 1.7320508075688772i```
+    pub(crate) fn cbrt(&self, context: &Context) -> Option<Number> {
+        match self {
+            Number::Float(_) => None,
+            Number::Decimal(value) => Some(Number::Decimal(
+                value.cbrt_with_context(context).normalized(),
+            )),
+            Number::Rational(value) => exact_root(value, 3).map(Number::Rational),
+            Number::Integer(value) => exact_root(&value.clone().into(), 3).map(integer_or_fraction),
+            Number::Complex(value) => Some(Number::Complex(value.cbrt())),
+        }
+    }
+
+    /// Apply a function to a complex number, None if the number isnt complex
+    pub(crate) fn map_complex(&self, function: fn(Complex64) -> Complex64) -> Option<Number> {
+        match self {
+            Number::Complex(value) => Some(Number::Complex(function(*value))),
+            _ => None,
+        }
+    }
+
+    /// The real part of a complex number, real numbers are returned as they are
+    pub(crate) fn real_part(&self) -> Number {
+        match self {
+            Number::Complex(value) => Number::Complex(value.re.into()),
+            number => number.clone(),
+        }
+    }
+
+    /// The imaginary part of a complex number, this is 0 for real numbers
+    pub(crate) fn imaginary_part(&self) -> Number {
+        match self {
+            Number::Float(_) => Number::Float(0.),
+            Number::Decimal(_) => Number::Decimal(BigDecimal::zero()),
+            Number::Rational(_) => Number::Rational(BigRational::zero()),
+            Number::Integer(_) => Number::Integer(BigInt::zero()),
+            Number::Complex(value) => Number::Complex(value.im.into()),
+        }
+    }
+
+    /// The distance from 0, this is real even for complex numbers
+    pub(crate) fn abs(&self) -> Number {
+        match self {
+            Number::Float(value) => Number::Float(value.abs()),
+            Number::Decimal(value) => Number::Decimal(value.abs()),
+            Number::Rational(value) => Number::Rational(value.abs()),
+            Number::Integer(value) => Number::Integer(value.abs()),
+            Number::Complex(value) => Number::Complex(value.norm().into()),
+        }
+    }
+
+    /// -1, 0 or 1, complex numbers are divided by their absolute value
+    pub(crate) fn sign(&self) -> Number {
+        match self {
+            Number::Float(value) => Number::Float(if *value == 0. { 0. } else { value.signum() }),
+            Number::Decimal(value) => Number::Decimal(value.signum()),
+            Number::Rational(value) => Number::Rational(value.signum()),
+            Number::Integer(value) => Number::Integer(value.signum()),
+            Number::Complex(value) if value.is_zero() => Number::Complex(*value),
+            Number::Complex(value) => Number::Complex(value / value.norm()),
+        }
+    }
+
+    /// Round to an integer, ```RoundingMode::HalfUp``` rounds halves away from 0 like ```f64::round```
+    /// Both parts of complex numbers are rounded
+    pub(crate) fn round_to_integer(&self, rounding: RoundingMode) -> Number {
+        match self {
+            Number::Float(value) => Number::Float(match rounding {
+                RoundingMode::Floor => value.floor(),
+                RoundingMode::Ceiling => value.ceil(),
+                RoundingMode::Down => value.trunc(),
+                _ => value.round(),
+            }),
+            Number::Decimal(value) => Number::Decimal(value.with_scale_round(0, rounding)),
+            Number::Rational(value) => Number::Rational(match rounding {
+                RoundingMode::Floor => value.floor(),
+                RoundingMode::Ceiling => value.ceil(),
+                RoundingMode::Down => value.trunc(),
+                _ => value.round(),
+            }),
+            Number::Integer(value) => Number::Integer(value.clone()),
+            Number::Complex(value) => {
+                let round = |part: f64| Number::Float(part).round_to_integer(rounding).to_f64();
+
+                Number::Complex(Complex64::new(round(value.re), round(value.im)))
+            }
+        }
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Number::Float(value) => Number::Float(-value),
+            Number::Decimal(value) => Number::Decimal(-value),
+            Number::Rational(value) => Number::Rational(-value),
+            Number::Integer(value) => Number::Integer(-value),
+            //Subtracting from 0 doesnt create a negative zero, the sign of a zero imaginary part changes the result of sqrt(-4)
+            Number::Complex(value) => Number::Complex(Complex64::zero() - value),
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match operands(self.clone(), other.clone()) {
+            Operands::Float(lhs, rhs) => lhs.partial_cmp(&rhs),
+            Operands::Decimal(lhs, rhs) => lhs.partial_cmp(&rhs),
+            Operands::Rational(lhs, rhs) => lhs.partial_cmp(&rhs),
+            Operands::Integer(lhs, rhs) => lhs.partial_cmp(&rhs),
+            //Only real numbers can be ordered
+            Operands::Complex(lhs, rhs) if lhs.im == 0. && rhs.im == 0. => {
+                lhs.re.partial_cmp(&rhs.re)
+            }
+            Operands::Complex(..) => None,
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Float(value) => write!(f, "{value}"),
+            //Decimals are only written in scientific notation if they would need too many zeros
+            Number::Decimal(value)
+                if value.order_of_magnitude().unsigned_abs() > MAX_PLAIN_DECIMAL_MAGNITUDE =>
+            {
+                f.write_str(&value.to_scientific_notation())
+            }
+            //A 0 can have zeros after it too, like 1e1000000 - 1e1000000
+            Number::Decimal(value) if value.is_zero() && value.fractional_digit_count() < 0 => {
+                f.write_str("0")
+            }
+            Number::Decimal(value) => f.write_str(&value.to_plain_string()),
+            //Fractions are written like 1/2, integers without the denominator
+            Number::Rational(value) => write!(f, "{value}"),
+            Number::Integer(value) => write!(f, "{value}"),
+            //a + bi, the parts which are 0 are left out
+            Number::Complex(Complex64 { re, im }) => {
+                //1i is written as i
+                let imaginary = |im: f64| {
+                    if im == 1. {
+                        "i".to_string()
+                    } else {
+                        format!("{im}i")
+                    }
+                };
+
+                if *im == 0. {
+                    write!(f, "{re}")
+                } else if *re == 0. && *im < 0. {
+                    write!(f, "-{}", imaginary(-im))
+                } else if *re == 0. {
+                    f.write_str(&imaginary(*im))
+                } else if *im < 0. {
+                    write!(f, "{re} - {}", imaginary(-im))
+                } else {
+                    write!(f, "{re} + {}", imaginary(*im))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,931 @@
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::{
+    number::Literal,
+    tokenizer::{Span, Token, TokenKind},
+    Calculator, CalculatorError, CalculatorErrorType, PercentMode, Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BinaryOperator {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division,
+    /// The remainder of a division
+    Modulo,
+    Power,
+
+    //Bitwise operators, these only work with integers
+    /// &
+    BitwiseAnd,
+    /// |
+    BitwiseOr,
+    /// xor
+    BitwiseXor,
+    /// <<
+    ShiftLeft,
+    /// >>
+    ShiftRight,
+
+    /// ```5 km in m``` or ```5 km to m```, the right side is the unit the left side is converted to
+    Conversion,
+}
+
+impl BinaryOperator {
+    /// The (left, right) binding power of the operator, the higher it is the tighter the operator binds
+    /// If the right power is higher than the left power the operator is left-associative, otherwise its right-associative
+    fn binding_power(self) -> (u8, u8) {
+        match self {
+            //The conversion binds the loosest: 5 km / 20 min in km/h converts the whole calculation
+            BinaryOperator::Conversion => (0, 1),
+            //The bitwise operators bind looser than arithmetic: 1 + 2 & 3 is (1 + 2) & 3
+            BinaryOperator::BitwiseOr => (1, 2),
+            BinaryOperator::BitwiseXor => (3, 4),
+            BinaryOperator::BitwiseAnd => (5, 6),
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => (7, 8),
+            BinaryOperator::Addition | BinaryOperator::Subtraction => (9, 10),
+            BinaryOperator::Multiplication | BinaryOperator::Division | BinaryOperator::Modulo => {
+                (11, 12)
+            }
+            //2^3^2 is 2^(3^2)
+            BinaryOperator::Power => (16, 15),
+        }
+    }
+
+    /// How the operator is written, implicit multiplications and ```of``` are ```*``` and conversions are ```in```
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            BinaryOperator::Addition => "+",
+            BinaryOperator::Subtraction => "-",
+            BinaryOperator::Multiplication => "*",
+            BinaryOperator::Division => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Power => "^",
+            BinaryOperator::BitwiseAnd => "&",
+            BinaryOperator::BitwiseOr => "|",
+            BinaryOperator::BitwiseXor => "xor",
+            BinaryOperator::ShiftLeft => "<<",
+            BinaryOperator::ShiftRight => ">>",
+            BinaryOperator::Conversion => "in",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum UnaryOperator {
+    /// -5
+    Minus,
+    /// +5
+    Plus,
+    /// ~5, this only works with integers
+    BitwiseNot,
+}
+
+impl UnaryOperator {
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            UnaryOperator::Minus => "-",
+            UnaryOperator::Plus => "+",
+            UnaryOperator::BitwiseNot => "~",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PostfixOperator {
+    /// ```10%``` when ```%``` means percentage
+    Percent,
+    /// 5!
+    Factorial,
+    /// 7!! = 7 * 5 * 3 * 1
+    DoubleFactorial,
+}
+
+impl PostfixOperator {
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            PostfixOperator::Percent => "%",
+            PostfixOperator::Factorial => "!",
+            PostfixOperator::DoubleFactorial => "!!",
+        }
+    }
+}
+
+/// The binding power of the unary operators, this is lower than ```^``` so ```-2^2``` is ```-(2^2)```
+const UNARY_BINDING_POWER: u8 = 13;
+
+/// The binding power of a unit right after a value like ```20 min```
+/// This is higher than ```*``` and ```/``` so ```5 km / 20 min``` is ```(5 km) / (20 min)```, but lower than ```^``` so ```2^3 m``` is ```(2^3) m``` while ```5 m^2``` is ```5 (m^2)```
+const UNIT_BINDING_POWER: (u8, u8) = (14, 15);
+
+/// The binding power of the postfix operators, these bind tighter than anything else
+const POSTFIX_BINDING_POWER: u8 = 17;
+
+/// Words which are read as operators like ```5 km in m```, so they cant be used as names
+const RESERVED_WORDS: [&str; 4] = ["in", "to", "xor", "of"];
+
+/// How deep the expression tree and the brackets can be nested, the parser and the evaluator recurse this deep
+pub(crate) const MAX_NESTING_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum NodeKind {
+    Number(Literal),
+    /// 2026-10-18
+    Date(NaiveDate),
+    /// 17:30, a time of the day
+    Time(NaiveTime),
+    /// A reference to a variable stored in the ```Calculator```
+    Variable(String),
+
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Node>,
+    },
+
+    Postfix {
+        operator: PostfixOperator,
+        operand: Box<Node>,
+    },
+
+    Binary {
+        operator: BinaryOperator,
+        lhs: Box<Node>,
+        rhs: Box<Node>,
+    },
+
+    /// ```max(1, 2)```
+    Call {
+        name: String,
+        arguments: Vec<Node>,
+    },
+
+    /// ```x = 3```, this can only be the root of the tree
+    Assignment {
+        name: String,
+        value: Box<Node>,
+    },
+
+    /// ```f(x, y) = x^2 + y```, this can only be the root of the tree
+    FunctionDefinition {
+        name: String,
+        parameters: Vec<String>,
+        body: Box<Node>,
+    },
+
+    /// A part of the input which couldnt be parsed, this is only used when recovering from errors
+    Error(CalculatorErrorType),
+}
+
+/// A node of the expression tree
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Node {
+    pub kind: NodeKind,
+    /// The part of the input this node was parsed from
+    pub span: Span,
+    /// How many levels of recursion calculating this node takes
+    /// Chains like ```1 + 2 + 3``` are calculated in a loop, so only their right operands add levels
+    pub depth: usize,
+}
+
+impl Node {
+    fn new(kind: NodeKind, span: Span) -> Self {
+        let depth = match &kind {
+            NodeKind::Binary { lhs, rhs, .. } if matches!(lhs.kind, NodeKind::Binary { .. }) => {
+                lhs.depth.max(rhs.depth + 1)
+            }
+            NodeKind::Binary { lhs, rhs, .. } => lhs.depth.max(rhs.depth) + 1,
+            NodeKind::Unary { operand, .. } | NodeKind::Postfix { operand, .. } => {
+                operand.depth + 1
+            }
+            NodeKind::Call { arguments, .. } => {
+                arguments
+                    .iter()
+                    .map(|argument| argument.depth)
+                    .max()
+                    .unwrap_or(0)
+                    + 1
+            }
+            NodeKind::Assignment { value, .. } => value.depth + 1,
+            NodeKind::FunctionDefinition { body, .. } => body.depth + 1,
+            _ => 1,
+        };
+
+        Self { kind, span, depth }
+    }
+
+    /// Take the kind out of the node, it is left as an empty leaf
+    pub(crate) fn take_kind(&mut self) -> NodeKind {
+        std::mem::replace(&mut self.kind, NodeKind::Variable(String::new()))
+    }
+}
+
+//Chains like 1 + 1 + ... + 1 are deeper than the stack, so the nodes are taken apart in a loop instead of dropping them recursively
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut kinds = match &self.kind {
+            NodeKind::Number(_)
+            | NodeKind::Date(_)
+            | NodeKind::Time(_)
+            | NodeKind::Variable(_)
+            | NodeKind::Error(_) => return,
+            _ => vec![self.take_kind()],
+        };
+
+        //The children are dropped once their own children have been taken out
+        while let Some(kind) = kinds.pop() {
+            match kind {
+                NodeKind::Unary { mut operand, .. } | NodeKind::Postfix { mut operand, .. } => {
+                    kinds.push(operand.take_kind());
+                }
+                NodeKind::Binary {
+                    mut lhs, mut rhs, ..
+                } => {
+                    kinds.push(lhs.take_kind());
+                    kinds.push(rhs.take_kind());
+                }
+                NodeKind::Call { arguments, .. } => {
+                    kinds.extend(
+                        arguments
+                            .into_iter()
+                            .map(|mut argument| argument.take_kind()),
+                    );
+                }
+                NodeKind::Assignment {
+                    value: mut child, ..
+                }
+                | NodeKind::FunctionDefinition {
+                    body: mut child, ..
+                } => kinds.push(child.take_kind()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Build the expression tree out of the token list
+/// The calculator is used to tell function calls like ```sqrt(2)``` apart from multiplications like ```x(2)```
+pub(crate) fn parse(tokens: Vec<Token>, input: &str, calculator: &Calculator) -> Result<Node> {
+    Parser::new(tokens, input, calculator, false).parse_statement()
+}
+
+/// Build the expression tree even if the tokens contain errors
+/// The parts which couldnt be parsed become ```NodeKind::Error``` nodes, and every error which was found is returned with the tree
+pub(crate) fn parse_with_recovery(
+    tokens: Vec<Token>,
+    input: &str,
+    calculator: &Calculator,
+) -> (Node, Vec<CalculatorError>) {
+    let mut parser = Parser::new(tokens, input, calculator, true);
+
+    let expression_tree = match parser.parse_statement() {
+        Ok(expression_tree) => expression_tree,
+        //Nesting too deeply stops the parser even when recovering
+        Err(error) => {
+            let node = Node::new(NodeKind::Error(error.err_type.clone()), error.span);
+
+            parser.errors.push(error);
+
+            node
+        }
+    };
+
+    (expression_tree, parser.errors)
+}
+
+/// A precedence climbing (Pratt) parser
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    /// The index of the next token
+    position: usize,
+    /// The input the tokens were made from, this is used for the errors
+    input: &'a str,
+    calculator: &'a Calculator,
+    /// The name of the function whose body is being parsed, so it can call itself
+    defined_function: Option<String>,
+    /// The parameters of that function, these hide units with the same name
+    parameters: Vec<String>,
+    /// How many calls of ```parse_expression``` are running, this grows with every bracket and unary operator
+    nesting: usize,
+    /// Whether errors are collected in ```errors``` instead of stopping the parser
+    recovering: bool,
+    errors: Vec<CalculatorError>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(
+        tokens: Vec<Token>,
+        input: &'a str,
+        calculator: &'a Calculator,
+        recovering: bool,
+    ) -> Self {
+        Self {
+            tokens,
+            position: 0,
+            input,
+            calculator,
+            defined_function: None,
+            parameters: Vec::new(),
+            nesting: 0,
+            recovering,
+            errors: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+
+        if token.is_some() {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    /// Whether an identifier is a unit like ```km``` and not a variable
+    fn is_unit(&self, name: &str) -> bool {
+        !self.parameters.iter().any(|parameter| parameter == name) && self.calculator.is_unit(name)
+    }
+
+    /// Whether an unknown name followed by a bracket in a function body is a function which is defined later, like ```b``` in ```a(x) = b(x)```
+    /// These calls are looked up when the body is calculated, so functions can call each other
+    fn is_later_function(&self, name: &str) -> bool {
+        self.defined_function.is_some()
+            && !self.parameters.iter().any(|parameter| parameter == name)
+            && self.calculator.lookup(name).is_none()
+    }
+
+    fn error(&self, error_type: CalculatorErrorType, span: Span) -> CalculatorError {
+        CalculatorError::new(error_type, span, self.input.to_string())
+    }
+
+    /// Return the error, or remember it and keep parsing when recovering from errors
+    fn report(&mut self, error_type: CalculatorErrorType, span: Span) -> Result<()> {
+        let error = self.error(error_type, span);
+
+        if !self.recovering {
+            return Err(error);
+        }
+
+        //The same problem can be found twice when a token is looked at again
+        if !self
+            .errors
+            .iter()
+            .any(|found| found.span == error.span && found.err_type == error.err_type)
+        {
+            self.errors.push(error);
+        }
+
+        Ok(())
+    }
+
+    /// Report the error and put an error node where the operand should have been
+    fn error_node(&mut self, error_type: CalculatorErrorType, span: Span) -> Result<Node> {
+        self.report(error_type.clone(), span)?;
+
+        Ok(Node::new(NodeKind::Error(error_type), span))
+    }
+
+    /// The input has ended while a token was still expected, this points right after the last token
+    fn end_of_input(&self) -> Span {
+        let end = self.tokens.last().map_or(0, |token| token.span.end);
+
+        Span::new(end, end)
+    }
+
+    /// Parse either an assignment, a function definition or an expression
+    fn parse_statement(&mut self) -> Result<Node> {
+        if let Some((name, name_span, parameters)) = self.parse_function_header()? {
+            self.defined_function = Some(name.clone());
+            self.parameters = parameters.clone();
+
+            let body = self.parse_root_expression()?;
+
+            let span = name_span.to(body.span);
+
+            return Ok(Node::new(
+                NodeKind::FunctionDefinition {
+                    name,
+                    parameters,
+                    body: Box::new(body),
+                },
+                span,
+            ));
+        }
+
+        if let [Token {
+            kind: TokenKind::Identifier(name),
+            span: name_span,
+        }, Token {
+            kind: TokenKind::Assignment,
+            ..
+        }, ..] = &self.tokens[self.position..]
+        {
+            let name = name.clone();
+            let name_span = *name_span;
+
+            //Skip the name and the =
+            self.position += 2;
+
+            self.check_name(&name, name_span)?;
+
+            let value = self.parse_root_expression()?;
+
+            let span = name_span.to(value.span);
+
+            return Ok(Node::new(
+                NodeKind::Assignment {
+                    name,
+                    value: Box::new(value),
+                },
+                span,
+            ));
+        }
+
+        self.parse_root_expression()
+    }
+
+    /// Parse an expression which has to go on until the end of the input
+    fn parse_root_expression(&mut self) -> Result<Node> {
+        let expression = self.parse_expression(0)?;
+
+        self.skip_unexpected(expression, |_| false)
+    }
+
+    /// Report the tokens after a finished expression which arent expected there
+    /// When recovering they are skipped and the rest of the input is parsed into the same expression
+    fn skip_unexpected(
+        &mut self,
+        mut expression: Node,
+        is_expected: fn(&TokenKind) -> bool,
+    ) -> Result<Node> {
+        while let Some(token) = self.peek().filter(|token| !is_expected(&token.kind)) {
+            //Either a ) which hasnt been opened, or a misplaced = or ,
+            let error_type = if token.kind == TokenKind::RightBracket {
+                CalculatorErrorType::UnbalancedBracket
+            } else {
+                CalculatorErrorType::UnexpectedToken
+            };
+
+            let span = token.span;
+
+            self.report(error_type, span)?;
+
+            self.advance();
+
+            expression = self.parse_infix(expression, 0)?;
+        }
+
+        Ok(expression)
+    }
+
+    /// Check whether the tokens start with ```f(x, y) =```, if they do skip them and return the name and the parameters
+    /// A parameter can only be written once, ```f(x, x) =``` is an error at the second ```x```
+    fn parse_function_header(&mut self) -> Result<Option<(String, Span, Vec<String>)>> {
+        let Some(header) = function_header(&self.tokens[self.position..]) else {
+            return Ok(None);
+        };
+
+        //Skip the whole header including the =
+        self.position += header.length;
+
+        self.check_name(&header.name, header.name_span)?;
+
+        for (index, (parameter, span)) in header
+            .parameters
+            .iter()
+            .zip(&header.parameter_spans)
+            .enumerate()
+        {
+            self.check_name(parameter, *span)?;
+
+            if header.parameters[..index].contains(parameter) {
+                self.report(
+                    CalculatorErrorType::DuplicateParameter(parameter.clone()),
+                    *span,
+                )?;
+            }
+        }
+
+        Ok(Some((header.name, header.name_span, header.parameters)))
+    }
+
+    /// Report a variable, function or parameter name which is a reserved word like ```in = 3```
+    fn check_name(&mut self, name: &str, span: Span) -> Result<()> {
+        if RESERVED_WORDS.contains(&name) {
+            self.report(CalculatorErrorType::ReservedName(name.to_string()), span)?;
+        }
+
+        Ok(())
+    }
+
+    /// Parse an expression which only contains operators binding tighter than ```min_binding_power```
+    fn parse_expression(&mut self, min_binding_power: u8) -> Result<Node> {
+        //Stop before the stack overflows, this is an error even when recovering
+        if self.nesting == MAX_NESTING_DEPTH {
+            let span = self.peek().map_or(self.end_of_input(), |token| token.span);
+
+            return Err(self.error(CalculatorErrorType::NestingLimit(MAX_NESTING_DEPTH), span));
+        }
+
+        self.nesting += 1;
+
+        let lhs = self.parse_prefix()?;
+        let expression = self.parse_infix(lhs, min_binding_power)?;
+
+        self.nesting -= 1;
+
+        Ok(expression)
+    }
+
+    /// Postfix operators like ```5!!!``` are parsed without recursion, but the tree they build still has to be evaluated recursively
+    fn check_depth(&self, node: &Node) -> Result<()> {
+        if node.depth > MAX_NESTING_DEPTH {
+            return Err(self.error(
+                CalculatorErrorType::NestingLimit(MAX_NESTING_DEPTH),
+                node.span,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Parse the operators following ```lhs``` which bind tighter than ```min_binding_power```
+    fn parse_infix(&mut self, mut lhs: Node, min_binding_power: u8) -> Result<Node> {
+        self.check_depth(&lhs)?;
+
+        while let Some(token) = self.peek() {
+            //Whether there is an invisible * between lhs and the next token
+            let mut implicit = false;
+
+            let operator = match token.kind {
+                TokenKind::Addition => BinaryOperator::Addition,
+                TokenKind::Subtraction => BinaryOperator::Subtraction,
+                TokenKind::Multiplication => BinaryOperator::Multiplication,
+                TokenKind::Division => BinaryOperator::Division,
+                TokenKind::Percent if self.calculator.percent_mode == PercentMode::Modulo => {
+                    BinaryOperator::Modulo
+                }
+                TokenKind::Percent | TokenKind::Factorial | TokenKind::DoubleFactorial => {
+                    if POSTFIX_BINDING_POWER < min_binding_power {
+                        break;
+                    }
+
+                    let operator = match token.kind {
+                        TokenKind::Percent => PostfixOperator::Percent,
+                        TokenKind::Factorial => PostfixOperator::Factorial,
+                        _ => PostfixOperator::DoubleFactorial,
+                    };
+
+                    let span = lhs.span.to(token.span);
+
+                    self.advance();
+
+                    lhs = Node::new(
+                        NodeKind::Postfix {
+                            operator,
+                            operand: Box::new(lhs),
+                        },
+                        span,
+                    );
+
+                    self.check_depth(&lhs)?;
+
+                    continue;
+                }
+                //50% of 80
+                TokenKind::Identifier(ref name)
+                    if name == "of" && self.calculator.percent_mode == PercentMode::Percentage =>
+                {
+                    BinaryOperator::Multiplication
+                }
+                TokenKind::Power => BinaryOperator::Power,
+                TokenKind::BitwiseAnd => BinaryOperator::BitwiseAnd,
+                TokenKind::BitwiseOr => BinaryOperator::BitwiseOr,
+                TokenKind::ShiftLeft => BinaryOperator::ShiftLeft,
+                TokenKind::ShiftRight => BinaryOperator::ShiftRight,
+                //5 xor 3
+                TokenKind::Identifier(ref name) if name == "xor" => BinaryOperator::BitwiseXor,
+                //5 km in m
+                TokenKind::Identifier(ref name) if name == "in" || name == "to" => {
+                    BinaryOperator::Conversion
+                }
+                //2(3), (2)(3), (2)3 and 2x all mean multiplication
+                TokenKind::Number(_)
+                | TokenKind::Date(_)
+                | TokenKind::Time(_)
+                | TokenKind::Invalid
+                | TokenKind::LeftBracket
+                | TokenKind::Identifier(_) => {
+                    implicit = true;
+
+                    BinaryOperator::Multiplication
+                }
+                //Let the caller deal with the closing bracket, the = and the , a ~ cant follow an operand
+                TokenKind::RightBracket
+                | TokenKind::Assignment
+                | TokenKind::Comma
+                | TokenKind::BitwiseNot => break,
+            };
+
+            let (left_binding_power, right_binding_power) = match token.kind {
+                TokenKind::Identifier(ref name) if implicit && self.is_unit(name) => {
+                    UNIT_BINDING_POWER
+                }
+                _ => operator.binding_power(),
+            };
+
+            if left_binding_power < min_binding_power {
+                break;
+            }
+
+            //Two numbers with only whitespace between them like 1 000 are most likely a typo, so they arent multiplied
+            if let TokenKind::Number(_) = token.kind {
+                let span = token.span;
+
+                if let Some(Token {
+                    kind: TokenKind::Number(_),
+                    span: previous,
+                }) = self.tokens.get(self.position - 1)
+                {
+                    if self.input[previous.end..span.start].trim().is_empty() {
+                        self.report(CalculatorErrorType::UnexpectedToken, span)?;
+                    }
+                }
+            }
+
+            //Implicit operators dont have a token we could skip
+            if !implicit {
+                self.advance();
+            }
+
+            let rhs = self.parse_expression(right_binding_power)?;
+
+            let span = lhs.span.to(rhs.span);
+
+            lhs = Node::new(
+                NodeKind::Binary {
+                    operator,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                },
+                span,
+            );
+
+            self.check_depth(&lhs)?;
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parse a number, a variable, a function call, a bracket or a unary operator with its operand
+    fn parse_prefix(&mut self) -> Result<Node> {
+        //If we have run out of tokens an operand is missing from the end of the input
+        let Some(token) = self.advance() else {
+            return self.error_node(
+                CalculatorErrorType::UnexpectedEndOfInput,
+                self.end_of_input(),
+            );
+        };
+
+        match token.kind {
+            TokenKind::Number(number) => Ok(Node::new(NodeKind::Number(number), token.span)),
+            TokenKind::Date(date) => Ok(Node::new(NodeKind::Date(date), token.span)),
+            TokenKind::Time(time) => Ok(Node::new(NodeKind::Time(time), token.span)),
+            TokenKind::Identifier(name) => {
+                let before_bracket = matches!(
+                    self.peek(),
+                    Some(Token {
+                        kind: TokenKind::LeftBracket,
+                        ..
+                    })
+                );
+
+                let is_call = before_bracket
+                    && (self.calculator.is_function(&name)
+                        || self.defined_function.as_deref() == Some(name.as_str())
+                        || self.is_later_function(&name));
+
+                if is_call {
+                    return self.parse_call(name, token.span);
+                }
+
+                let is_unknown = !self.parameters.contains(&name)
+                    && self.calculator.lookup(&name).is_none()
+                    && !self.calculator.exchange_rates.is_currency(&name);
+
+                //An unknown name or a unit followed by a bracket like h(1) is most likely a function which doesnt exist
+                //When recovering it is kept as a multiplication
+                if before_bracket && (is_unknown || self.is_unit(&name)) {
+                    self.report(
+                        CalculatorErrorType::UnknownFunction(name.clone()),
+                        token.span,
+                    )?;
+                }
+
+                //A function without brackets like sin 0
+                if !before_bracket
+                    && is_unknown
+                    && (self.calculator.is_function(&name)
+                        || self.defined_function.as_deref() == Some(name.as_str()))
+                {
+                    self.report(
+                        CalculatorErrorType::MissingArgumentBrackets(name.clone()),
+                        token.span,
+                    )?;
+                }
+
+                Ok(Node::new(NodeKind::Variable(name), token.span))
+            }
+            TokenKind::Subtraction | TokenKind::Addition | TokenKind::BitwiseNot => {
+                let operator = match token.kind {
+                    TokenKind::Subtraction => UnaryOperator::Minus,
+                    TokenKind::Addition => UnaryOperator::Plus,
+                    _ => UnaryOperator::BitwiseNot,
+                };
+
+                let operand = self.parse_expression(UNARY_BINDING_POWER)?;
+
+                let span = token.span.to(operand.span);
+
+                Ok(Node::new(
+                    NodeKind::Unary {
+                        operator,
+                        operand: Box::new(operand),
+                    },
+                    span,
+                ))
+            }
+            TokenKind::LeftBracket => {
+                let inner = self.parse_expression(0)?;
+                let mut inner =
+                    self.skip_unexpected(inner, |kind| *kind == TokenKind::RightBracket)?;
+
+                //The bracket itself doesnt need a node, only its span is kept
+                match self.advance() {
+                    Some(right_bracket) => Ok(Node::new(
+                        inner.take_kind(),
+                        token.span.to(right_bracket.span),
+                    )),
+                    //The bracket was left open
+                    None => {
+                        self.report(CalculatorErrorType::UnbalancedBracket, token.span)?;
+
+                        Ok(Node::new(inner.take_kind(), token.span.to(inner.span)))
+                    }
+                }
+            }
+            //The tokenizer has already reported why the literal is invalid
+            TokenKind::Invalid => Ok(Node::new(
+                NodeKind::Error(CalculatorErrorType::InvalidNumberLiteral),
+                token.span,
+            )),
+            //The operand is missing before a ) or a , so leave them to the caller
+            TokenKind::RightBracket | TokenKind::Comma => {
+                self.position -= 1;
+
+                self.error_node(CalculatorErrorType::UnexpectedToken, token.span)
+            }
+            //Skip the token which cant start an operand and try the next one
+            _ => {
+                self.report(CalculatorErrorType::UnexpectedToken, token.span)?;
+
+                self.parse_prefix()
+            }
+        }
+    }
+
+    /// Parse the arguments of a function call, the next token has to be the (
+    fn parse_call(&mut self, name: String, name_span: Span) -> Result<Node> {
+        //Skip the (
+        let left_bracket = self.advance().ok_or_else(|| {
+            self.error(
+                CalculatorErrorType::UnexpectedEndOfInput,
+                self.end_of_input(),
+            )
+        })?;
+
+        let mut arguments = Vec::new();
+
+        //The function may not have any arguments
+        if let Some(Token {
+            kind: TokenKind::RightBracket,
+            span,
+        }) = self.peek()
+        {
+            let span = name_span.to(*span);
+
+            self.advance();
+
+            return Ok(Node::new(NodeKind::Call { name, arguments }, span));
+        }
+
+        loop {
+            let argument = self.parse_expression(0)?;
+
+            arguments.push(self.skip_unexpected(argument, |kind| {
+                matches!(kind, TokenKind::Comma | TokenKind::RightBracket)
+            })?);
+
+            match self.advance() {
+                Some(Token {
+                    kind: TokenKind::Comma,
+                    ..
+                }) => continue,
+                Some(right_bracket) => {
+                    return Ok(Node::new(
+                        NodeKind::Call { name, arguments },
+                        name_span.to(right_bracket.span),
+                    ))
+                }
+                //The bracket was left open
+                None => {
+                    self.report(CalculatorErrorType::UnbalancedBracket, left_bracket.span)?;
+
+                    let span = arguments
+                        .last()
+                        .map_or(left_bracket.span, |argument| argument.span);
+
+                    return Ok(Node::new(
+                        NodeKind::Call { name, arguments },
+                        name_span.to(span),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// The start of a function definition like ```f(x, y) =```
+pub(crate) struct FunctionHeader {
+    pub name: String,
+    pub name_span: Span,
+    pub parameters: Vec<String>,
+    /// Where the parameters are written, in the same order
+    pub parameter_spans: Vec<Span>,
+    /// The number of tokens including the =
+    pub length: usize,
+}
+
+/// Check whether the tokens start with ```f(x, y) =```
+pub(crate) fn function_header(tokens: &[Token]) -> Option<FunctionHeader> {
+    let Some(Token {
+        kind: TokenKind::Identifier(name),
+        span: name_span,
+    }) = tokens.first()
+    else {
+        return None;
+    };
+
+    if tokens.get(1)?.kind != TokenKind::LeftBracket {
+        return None;
+    }
+
+    let mut parameters = Vec::new();
+    let mut parameter_spans = Vec::new();
+
+    //Points at the first token after the (
+    let mut index = 2;
+
+    //f() = ... doesnt have any parameters
+    if tokens.get(index)?.kind != TokenKind::RightBracket {
+        //The parameters are identifiers separated by commas
+        loop {
+            let Token {
+                kind: TokenKind::Identifier(parameter),
+                span,
+            } = tokens.get(index)?
+            else {
+                return None;
+            };
+
+            parameters.push(parameter.clone());
+            parameter_spans.push(*span);
+
+            index += 1;
+
+            match tokens.get(index)?.kind {
+                TokenKind::Comma => index += 1,
+                TokenKind::RightBracket => break,
+                _ => return None,
+            }
+        }
+    }
+
+    //index points at the ) now
+    if tokens.get(index + 1)?.kind != TokenKind::Assignment {
+        return None;
+    }
+
+    Some(FunctionHeader {
+        name: name.clone(),
+        name_span: *name_span,
+        parameters,
+        parameter_spans,
+        length: index + 2,
+    })
+}
@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+use crate::{
+    parser::{Node, NodeKind},
+    CalculatorErrorType, Diagnostic,
+};
+
+/// The result of ```Calculator::parse_with_recovery```, the tree is built even if the input has problems
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredParse {
+    /// The parts of the input which couldnt be parsed are ```SyntaxKind::Error``` nodes
+    pub tree: SyntaxNode,
+    /// Every problem which was found, sorted by where they are in the input
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A node of the expression tree, brackets dont have their own nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    /// The byte range of the input this node was parsed from
+    pub span: Range<usize>,
+    /// The operands of an operator, the arguments of a call, or the value of an assignment or definition
+    pub children: Vec<SyntaxNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxKind {
+    Number,
+    /// 2026-10-18
+    Date,
+    /// 17:30
+    Time,
+    /// A variable, constant, unit or currency
+    Variable(String),
+    /// An operator like ```+```, ```-``` or ```!```, unary and postfix operators have one child and binary operators two
+    /// Implicit multiplications like ```2x``` are ```*``` and conversions are ```in```
+    Operator(&'static str),
+    /// ```max(1, 2)```
+    Call(String),
+    /// ```x = 3```
+    Assignment(String),
+    /// ```f(x, y) = x^2 + y```
+    FunctionDefinition {
+        name: String,
+        parameters: Vec<String>,
+    },
+    /// A part of the input which couldnt be parsed, like a missing operand or an invalid number
+    Error(CalculatorErrorType),
+}
+
+/// The kind of a node and the nodes below it
+fn kind_and_children(node: &Node) -> (SyntaxKind, Vec<&Node>) {
+    match &node.kind {
+        NodeKind::Number(_) => (SyntaxKind::Number, Vec::new()),
+        NodeKind::Date(_) => (SyntaxKind::Date, Vec::new()),
+        NodeKind::Time(_) => (SyntaxKind::Time, Vec::new()),
+        NodeKind::Variable(name) => (SyntaxKind::Variable(name.clone()), Vec::new()),
+        NodeKind::Unary { operator, operand } => {
+            (SyntaxKind::Operator(operator.symbol()), vec![operand])
+        }
+        NodeKind::Postfix { operator, operand } => {
+            (SyntaxKind::Operator(operator.symbol()), vec![operand])
+        }
+        NodeKind::Binary { operator, lhs, rhs } => {
+            (SyntaxKind::Operator(operator.symbol()), vec![lhs, rhs])
+        }
+        NodeKind::Call { name, arguments } => {
+            (SyntaxKind::Call(name.clone()), arguments.iter().collect())
+        }
+        NodeKind::Assignment { name, value } => (SyntaxKind::Assignment(name.clone()), vec![value]),
+        NodeKind::FunctionDefinition {
+            name,
+            parameters,
+            body,
+        } => (
+            SyntaxKind::FunctionDefinition {
+                name: name.clone(),
+                parameters: parameters.clone(),
+            },
+            vec![body],
+        ),
+        NodeKind::Error(error_type) => (SyntaxKind::Error(error_type.clone()), Vec::new()),
+    }
+}
+
+enum Visit<'a> {
+    Node(&'a Node),
+    /// All children of the node have been converted and are at the end of the finished nodes
+    Finish(SyntaxKind, Range<usize>, usize),
+}
+
+//Chains like 1 + 1 + ... + 1 are deeper than the stack, so the tree is converted in a loop instead of recursively
+impl From<&Node> for SyntaxNode {
+    fn from(node: &Node) -> Self {
+        let mut visits = vec![Visit::Node(node)];
+        let mut finished: Vec<SyntaxNode> = Vec::new();
+
+        while let Some(visit) = visits.pop() {
+            match visit {
+                Visit::Node(node) => {
+                    let (kind, children) = kind_and_children(node);
+                    visits.push(Visit::Finish(kind, node.span.range(), children.len()));
+                    //The first child is converted first, so its node ends up first
+                    visits.extend(children.into_iter().rev().map(Visit::Node));
+                }
+                Visit::Finish(kind, span, child_count) => {
+                    let children = finished.split_off(finished.len() - child_count);
+                    finished.push(Self {
+                        kind,
+                        span,
+                        children,
+                    });
+                }
+            }
+        }
+
+        finished
+            .pop()
+            .expect("The root node is always finished last")
+    }
+}
+
+//The tree is as deep as the expression it was parsed from, so it is taken apart in a loop too
+impl Drop for SyntaxNode {
+    fn drop(&mut self) {
+        let mut children = std::mem::take(&mut self.children);
+
+        while let Some(mut child) = children.pop() {
+            children.append(&mut child.children);
+        }
+    }
+}
@@ -0,0 +1,465 @@
+use std::ops::Range;
+
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, NaiveTime};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::{number::Literal, CalculatorError, CalculatorErrorType, Result};
+
+/// A range of the input, ```start``` is inclusive while ```end``` is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The span as a range, so it can index the input: ```&input[span.range()]```
+    pub fn range(self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Create a span which covers both ```self``` and ```other```
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division,
+    /// %, this is either the remainder of a division or a percentage
+    Percent,
+    Power,
+    /// &
+    BitwiseAnd,
+    /// |
+    BitwiseOr,
+    /// ~
+    BitwiseNot,
+    /// <<
+    ShiftLeft,
+    /// >>
+    ShiftRight,
+    /// !
+    Factorial,
+    /// !!, this is a separate token so ```(5!)!``` is different from ```5!!```
+    DoubleFactorial,
+
+    /// (
+    LeftBracket,
+    /// )
+    RightBracket,
+
+    /// =
+    Assignment,
+    /// , separates the arguments of functions
+    Comma,
+
+    Number(Literal),
+    /// 2026-10-18
+    Date(NaiveDate),
+    /// 17:30 or 09:15:30
+    Time(NaiveTime),
+    /// Names of variables, for example ```rate```
+    Identifier(String),
+    /// A number, date or time which couldnt be parsed, like ```1.2.3```, this is only kept when recovering from errors
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    /// Where the token is in the input
+    pub span: Span,
+}
+
+pub(crate) fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let (tokens, errors) = tokenize_with_recovery(input);
+
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(tokens),
+    }
+}
+
+/// Tokenize the whole input even if it contains errors, unknown characters are left out and literals which cant be parsed become ```TokenKind::Invalid```
+pub(crate) fn tokenize_with_recovery(input: &str) -> (Vec<Token>, Vec<CalculatorError>) {
+    let mut final_list: Vec<Token> = Vec::new();
+
+    let mut errors: Vec<CalculatorError> = Vec::new();
+
+    let mut number_buffer: String = String::new();
+
+    //The index where the number in the buffer has started
+    let mut number_start = 0;
+
+    let mut identifier_buffer: String = String::new();
+
+    //The index where the identifier in the buffer has started
+    let mut identifier_start = 0;
+
+    //Set when a token longer than one character has been found, so the rest of it isnt tokenized again
+    let mut skip_until = 0;
+
+    for (index, char) in input.char_indices() {
+        if index < skip_until {
+            continue;
+        }
+
+        //Hexadecimal, octal and binary literals: 0xFF, 0o777, 0b1010
+        if radix(&number_buffer).is_some() && char.is_ascii_alphanumeric() {
+            number_buffer.push(char);
+
+            continue;
+        }
+
+        if number_buffer == "0" && is_radix_prefix(char, &input[index..]) {
+            number_buffer.push(char);
+
+            continue;
+        }
+
+        //The exponent of scientific notation like 6.022e23, otherwise the e is the constant: 2e
+        if !number_buffer.is_empty() && is_exponent(&number_buffer, char, &input[index..]) {
+            number_buffer.push(char);
+
+            continue;
+        }
+
+        //An identifier starts with a letter or _, after that it can contain digits too
+        if char.is_alphabetic()
+            || char == '_'
+            || (char.is_ascii_digit() && !identifier_buffer.is_empty())
+        {
+            //A number right before an identifier gets multiplied with it later: 2x
+            if !number_buffer.is_empty() {
+                push_or_recover(
+                    parse_number(&number_buffer, number_start, input),
+                    &mut final_list,
+                    &mut errors,
+                );
+
+                number_buffer.clear();
+            }
+
+            if identifier_buffer.is_empty() {
+                identifier_start = index;
+            }
+
+            identifier_buffer.push(char);
+
+            continue;
+        }
+
+        //The identifier has ended
+        if !identifier_buffer.is_empty() {
+            final_list.push(Token {
+                kind: TokenKind::Identifier(identifier_buffer.clone()),
+                span: Span::new(identifier_start, index),
+            });
+
+            identifier_buffer.clear();
+        }
+
+        //Dates and times are whole tokens, so the - in 2026-10-18 isnt a subtraction
+        if number_buffer.is_empty() {
+            if let Some(length) = date_or_time_length(&input[index..]) {
+                let literal = &input[index..index + length];
+                let token = parse_date_or_time(literal, index, input);
+
+                //Dates which dont exist like 1234-56-78 are subtractions of numbers, like they were before dates were added
+                if token.is_ok() || !literal.contains('-') {
+                    push_or_recover(token, &mut final_list, &mut errors);
+
+                    skip_until = index + length;
+
+                    continue;
+                }
+            }
+        }
+
+        //. means we are defining a float, self explnatory
+        if char.is_ascii_digit() || char == '.' {
+            if number_buffer.is_empty() {
+                number_start = index;
+            }
+
+            //Push back char to the buffer
+            number_buffer.push(char);
+
+            continue;
+        }
+
+        //If its anything else then we need to push back the buffer, then we should clean it
+        if !number_buffer.is_empty() {
+            push_or_recover(
+                parse_number(&number_buffer, number_start, input),
+                &mut final_list,
+                &mut errors,
+            );
+
+            //Clear buffer
+            number_buffer.clear();
+        }
+
+        //Whitespace only separates tokens, like the two words in ```50% of 80```
+        if char.is_whitespace() {
+            continue;
+        }
+
+        //Merge two ! which are right next to each other
+        if char == '!' {
+            if let Some(
+                last_token @ Token {
+                    kind: TokenKind::Factorial,
+                    ..
+                },
+            ) = final_list.last_mut()
+            {
+                if last_token.span.end == index {
+                    last_token.kind = TokenKind::DoubleFactorial;
+                    last_token.span.end += 1;
+
+                    continue;
+                }
+            }
+        }
+
+        //Recognize char if its an expression
+        let kind = match char {
+            '+' => TokenKind::Addition,
+            '-' => TokenKind::Subtraction,
+            '/' => TokenKind::Division,
+            '%' => TokenKind::Percent,
+            '*' => TokenKind::Multiplication,
+            '^' => TokenKind::Power,
+            '!' => TokenKind::Factorial,
+            '&' => TokenKind::BitwiseAnd,
+            '|' => TokenKind::BitwiseOr,
+            '~' => TokenKind::BitwiseNot,
+            //The second half of << and >> is skipped by the next iteration
+            '<' | '>' if input[index + 1..].starts_with(char) => {
+                skip_until = index + 2;
+
+                if char == '<' {
+                    TokenKind::ShiftLeft
+                } else {
+                    TokenKind::ShiftRight
+                }
+            }
+            ')' => TokenKind::RightBracket,
+            '(' => TokenKind::LeftBracket,
+            '=' => TokenKind::Assignment,
+            ',' => TokenKind::Comma,
+            //The character is left out, so the rest of the input can still be tokenized
+            _ => {
+                errors.push(CalculatorError::new(
+                    CalculatorErrorType::InvalidCharacter,
+                    Span::new(index, index + char.len_utf8()),
+                    input.to_string(),
+                ));
+
+                continue;
+            }
+        };
+
+        //<< and >> are two characters long
+        let end = skip_until.max(index + char.len_utf8());
+
+        final_list.push(Token {
+            kind,
+            span: Span::new(index, end),
+        });
+    }
+
+    //If num buffer is not empty we should push it back, to save the last number
+    if !number_buffer.is_empty() {
+        push_or_recover(
+            parse_number(&number_buffer, number_start, input),
+            &mut final_list,
+            &mut errors,
+        );
+    }
+
+    //Same goes for the last identifier
+    if !identifier_buffer.is_empty() {
+        final_list.push(Token {
+            span: Span::new(identifier_start, identifier_start + identifier_buffer.len()),
+            kind: TokenKind::Identifier(identifier_buffer),
+        });
+    }
+
+    (final_list, errors)
+}
+
+/// Push the token, or remember the error and push an invalid token in its place
+fn push_or_recover(
+    token: Result<Token>,
+    tokens: &mut Vec<Token>,
+    errors: &mut Vec<CalculatorError>,
+) {
+    match token {
+        Ok(token) => tokens.push(token),
+        Err(error) => {
+            tokens.push(Token {
+                kind: TokenKind::Invalid,
+                span: error.span,
+            });
+
+            errors.push(error);
+        }
+    }
+}
+
+/// Check whether ```char``` continues the exponent of the number in the buffer, ```rest``` is the input starting from ```char```
+/// An e is only an exponent if its followed by a digit or a sign and a digit: ```1e5``` and ```2.5e+3```, but ```2e``` and ```2e + 3``` contain the constant e
+fn is_exponent(number_buffer: &str, char: char, rest: &str) -> bool {
+    //In hexadecimal literals like ```0xE+1``` the e is a digit, radix literals dont have exponents
+    if radix(number_buffer).is_some() {
+        return false;
+    }
+
+    match char {
+        'e' | 'E' => {
+            //There can only be one exponent
+            if number_buffer.contains(['e', 'E']) {
+                return false;
+            }
+
+            let mut following = rest.chars().skip(1);
+
+            match following.next() {
+                Some('+' | '-') => following.next().is_some_and(|char| char.is_ascii_digit()),
+                Some(char) => char.is_ascii_digit(),
+                None => false,
+            }
+        }
+        //The sign of the exponent
+        '+' | '-' => number_buffer.ends_with(['e', 'E']),
+        _ => false,
+    }
+}
+
+/// The length of the date like ```2026-10-18``` or the time like ```17:30``` or ```09:15:30``` at the start of ```rest```
+fn date_or_time_length(rest: &str) -> Option<usize> {
+    //How many digits there are from the index on
+    let digits = |index: usize| {
+        rest.as_bytes()
+            .iter()
+            .skip(index)
+            .take_while(|byte| byte.is_ascii_digit())
+            .count()
+    };
+
+    let separator = |index: usize, separator: u8| rest.as_bytes().get(index) == Some(&separator);
+
+    //2026-10-18
+    if digits(0) == 4
+        && separator(4, b'-')
+        && digits(5) == 2
+        && separator(7, b'-')
+        && digits(8) == 2
+    {
+        return Some(10);
+    }
+
+    //17:30, the seconds are optional
+    let hours = digits(0);
+
+    if !(1..=2).contains(&hours) || !separator(hours, b':') || digits(hours + 1) != 2 {
+        return None;
+    }
+
+    if separator(hours + 3, b':') && digits(hours + 4) == 2 {
+        return Some(hours + 6);
+    }
+
+    Some(hours + 3)
+}
+
+/// Turn a date or a time into a token, this fails for dates and times which dont exist like ```2026-02-30``` or ```25:00```
+fn parse_date_or_time(literal: &str, start: usize, input: &str) -> Result<Token> {
+    let span = Span::new(start, start + literal.len());
+
+    let kind = if literal.contains('-') {
+        NaiveDate::parse_from_str(literal, "%Y-%m-%d")
+            .ok()
+            .map(TokenKind::Date)
+    } else {
+        NaiveTime::parse_from_str(literal, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(literal, "%H:%M"))
+            .ok()
+            .map(TokenKind::Time)
+    };
+
+    match kind {
+        Some(kind) => Ok(Token { kind, span }),
+        None => Err(CalculatorError::new(
+            CalculatorErrorType::InvalidNumberLiteral,
+            span,
+            input.to_string(),
+        )),
+    }
+}
+
+/// The radix of the number in the buffer if it has a prefix like 0x
+fn radix(number_buffer: &str) -> Option<u32> {
+    match number_buffer.get(..2)? {
+        "0x" => Some(16),
+        "0o" => Some(8),
+        "0b" => Some(2),
+        _ => None,
+    }
+}
+
+/// Check whether ```char``` is the x, o or b of a radix prefix, ```rest``` is the input starting from ```char```
+/// A prefix followed by a name like ```0bytes``` is 0 multiplied by the name,
+/// otherwise it starts a literal even without valid digits, so ```0x``` and ```0b2``` are invalid literals
+fn is_radix_prefix(char: char, rest: &str) -> bool {
+    let radix = match char {
+        'x' => 16,
+        'o' => 8,
+        'b' => 2,
+        _ => return false,
+    };
+
+    rest.chars()
+        .nth(1)
+        .is_none_or(|char| char.is_digit(radix) || !(char.is_alphabetic() || char == '_'))
+}
+
+/// Turn the contents of the number buffer into a token, this fails for inputs like ```1.2.3``` or ```0b102```
+fn parse_number(number_buffer: &str, start: usize, input: &str) -> Result<Token> {
+    let span = Span::new(start, start + number_buffer.len());
+
+    let literal = match radix(number_buffer) {
+        Some(radix) => {
+            BigInt::parse_bytes(&number_buffer.as_bytes()[2..], radix).map(|integer| Literal {
+                float: integer.to_f64().unwrap_or(f64::INFINITY),
+                exact: BigDecimal::from(integer),
+            })
+        }
+        None => Literal::parse(number_buffer),
+    };
+
+    match literal {
+        Some(literal) => Ok(Token {
+            kind: TokenKind::Number(literal),
+            span,
+        }),
+        None => Err(CalculatorError::new(
+            CalculatorErrorType::InvalidNumberLiteral,
+            span,
+            input.to_string(),
+        )),
+    }
+}
@@ -0,0 +1,724 @@
+use std::{fmt::Display, ops::Neg};
+
+use bigdecimal::Context;
+
+use crate::number::{Literal, Number, NumberMode};
+
+/// The exponents of the SI base dimensions: length, mass, time, electric current, temperature, amount of substance and luminous intensity, and of money
+/// The dimension of ```m/s^2``` is ```[1, 0, -2, 0, 0, 0, 0, 0]```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Dimension([i32; 8]);
+
+impl Dimension {
+    fn mul(self, rhs: Dimension) -> Dimension {
+        let mut exponents = self.0;
+
+        for (exponent, rhs_exponent) in exponents.iter_mut().zip(rhs.0) {
+            *exponent += rhs_exponent;
+        }
+
+        Dimension(exponents)
+    }
+
+    fn pow(self, exponent: i32) -> Dimension {
+        Dimension(self.0.map(|base_exponent| base_exponent * exponent))
+    }
+}
+
+const DIMENSIONLESS: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0, 0]);
+const LENGTH: Dimension = Dimension([1, 0, 0, 0, 0, 0, 0, 0]);
+const MASS: Dimension = Dimension([0, 1, 0, 0, 0, 0, 0, 0]);
+const TIME: Dimension = Dimension([0, 0, 1, 0, 0, 0, 0, 0]);
+const CURRENT: Dimension = Dimension([0, 0, 0, 1, 0, 0, 0, 0]);
+const TEMPERATURE: Dimension = Dimension([0, 0, 0, 0, 1, 0, 0, 0]);
+const AMOUNT: Dimension = Dimension([0, 0, 0, 0, 0, 1, 0, 0]);
+const LUMINOUS_INTENSITY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 1, 0]);
+const MONEY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0, 1]);
+const VOLUME: Dimension = Dimension([3, 0, 0, 0, 0, 0, 0, 0]);
+const SPEED: Dimension = Dimension([1, 0, -1, 0, 0, 0, 0, 0]);
+const FREQUENCY: Dimension = Dimension([0, 0, -1, 0, 0, 0, 0, 0]);
+const FORCE: Dimension = Dimension([1, 1, -2, 0, 0, 0, 0, 0]);
+const PRESSURE: Dimension = Dimension([-1, 1, -2, 0, 0, 0, 0, 0]);
+const ENERGY: Dimension = Dimension([2, 1, -2, 0, 0, 0, 0, 0]);
+const POWER: Dimension = Dimension([2, 1, -3, 0, 0, 0, 0, 0]);
+const CHARGE: Dimension = Dimension([0, 0, 1, 1, 0, 0, 0, 0]);
+const VOLTAGE: Dimension = Dimension([2, 1, -3, -1, 0, 0, 0, 0]);
+
+/// A unit which can be used in any calculation, like ```5 km``` or ```20 minutes```
+struct UnitDefinition {
+    /// Short names like ```m```, these can have an SI prefix if the unit is ```prefixable```
+    symbols: &'static [&'static str],
+    /// Long names like ```metres```, these cant have a prefix
+    names: &'static [&'static str],
+    /// The size of the unit in SI units, this is a string so the conversions are exact with exact numbers
+    scale: &'static str,
+    dimension: Dimension,
+    prefixable: bool,
+}
+
+const UNITS: &[UnitDefinition] = &[
+    //SI base units, the kilogram is the SI unit of mass but the prefixes go in front of the gram
+    UnitDefinition {
+        symbols: &["m"],
+        names: &["metre", "metres", "meter", "meters"],
+        scale: "1",
+        dimension: LENGTH,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["g"],
+        names: &["gram", "grams"],
+        scale: "0.001",
+        dimension: MASS,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["s"],
+        names: &["second", "seconds", "sec"],
+        scale: "1",
+        dimension: TIME,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["A"],
+        names: &["ampere", "amperes"],
+        scale: "1",
+        dimension: CURRENT,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["K"],
+        names: &["kelvin"],
+        scale: "1",
+        dimension: TEMPERATURE,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["mol"],
+        names: &["mole", "moles"],
+        scale: "1",
+        dimension: AMOUNT,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["cd"],
+        names: &["candela"],
+        scale: "1",
+        dimension: LUMINOUS_INTENSITY,
+        prefixable: true,
+    },
+    //Derived SI units
+    UnitDefinition {
+        symbols: &["L", "l"],
+        names: &["litre", "litres", "liter", "liters"],
+        scale: "0.001",
+        dimension: VOLUME,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["Hz"],
+        names: &["hertz"],
+        scale: "1",
+        dimension: FREQUENCY,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["N"],
+        names: &["newton", "newtons"],
+        scale: "1",
+        dimension: FORCE,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["Pa"],
+        names: &["pascal", "pascals"],
+        scale: "1",
+        dimension: PRESSURE,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["J"],
+        names: &["joule", "joules"],
+        scale: "1",
+        dimension: ENERGY,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["W"],
+        names: &["watt", "watts"],
+        scale: "1",
+        dimension: POWER,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["C"],
+        names: &["coulomb", "coulombs"],
+        scale: "1",
+        dimension: CHARGE,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["V"],
+        names: &["volt", "volts"],
+        scale: "1",
+        dimension: VOLTAGE,
+        prefixable: true,
+    },
+    //Time
+    UnitDefinition {
+        symbols: &["min"],
+        names: &["minute", "minutes"],
+        scale: "60",
+        dimension: TIME,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["h"],
+        names: &["hour", "hours", "hr"],
+        scale: "3600",
+        dimension: TIME,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["d"],
+        names: &["day", "days"],
+        scale: "86400",
+        dimension: TIME,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &[],
+        names: &["week", "weeks"],
+        scale: "604800",
+        dimension: TIME,
+        prefixable: false,
+    },
+    //Imperial units, the inch doesnt have a symbol because ```in``` converts units
+    UnitDefinition {
+        symbols: &[],
+        names: &["inch", "inches"],
+        scale: "0.0254",
+        dimension: LENGTH,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["ft"],
+        names: &["foot", "feet"],
+        scale: "0.3048",
+        dimension: LENGTH,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["yd"],
+        names: &["yard", "yards"],
+        scale: "0.9144",
+        dimension: LENGTH,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["mi"],
+        names: &["mile", "miles"],
+        scale: "1609.344",
+        dimension: LENGTH,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["lb", "lbs"],
+        names: &["pound", "pounds"],
+        scale: "0.45359237",
+        dimension: MASS,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["oz"],
+        names: &["ounce", "ounces"],
+        scale: "0.028349523125",
+        dimension: MASS,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["gal"],
+        names: &["gallon", "gallons"],
+        scale: "0.003785411784",
+        dimension: VOLUME,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["mph"],
+        names: &[],
+        scale: "0.44704",
+        dimension: SPEED,
+        prefixable: false,
+    },
+    //Other units
+    UnitDefinition {
+        symbols: &["t"],
+        names: &["tonne", "tonnes"],
+        scale: "1000",
+        dimension: MASS,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["bar"],
+        names: &[],
+        scale: "100000",
+        dimension: PRESSURE,
+        prefixable: true,
+    },
+    UnitDefinition {
+        symbols: &["Wh"],
+        names: &[],
+        scale: "3600",
+        dimension: ENERGY,
+        prefixable: true,
+    },
+    //Angles dont have a dimension, so sin(90 deg) works
+    UnitDefinition {
+        symbols: &["rad"],
+        names: &["radian", "radians"],
+        scale: "1",
+        dimension: DIMENSIONLESS,
+        prefixable: false,
+    },
+    UnitDefinition {
+        symbols: &["deg"],
+        names: &["degree", "degrees"],
+        //pi/180 isnt a decimal, so degrees are never exact
+        scale: "0.017453292519943295769236907684886",
+        dimension: DIMENSIONLESS,
+        prefixable: false,
+    },
+];
+
+/// SI prefixes and their factors, da comes first so ```dam``` isnt read as deci-am
+const PREFIXES: &[(&str, &str)] = &[
+    ("da", "1e1"),
+    ("Q", "1e30"),
+    ("R", "1e27"),
+    ("Y", "1e24"),
+    ("Z", "1e21"),
+    ("E", "1e18"),
+    ("P", "1e15"),
+    ("T", "1e12"),
+    ("G", "1e9"),
+    ("M", "1e6"),
+    ("k", "1e3"),
+    ("h", "1e2"),
+    ("d", "1e-1"),
+    ("c", "1e-2"),
+    ("m", "1e-3"),
+    ("µ", "1e-6"),
+    ("u", "1e-6"),
+    ("n", "1e-9"),
+    ("p", "1e-12"),
+    ("f", "1e-15"),
+    ("a", "1e-18"),
+    ("z", "1e-21"),
+    ("y", "1e-24"),
+    ("r", "1e-27"),
+    ("q", "1e-30"),
+];
+
+/// The symbols and names of the units without prefixes, these are suggested for misspelled names
+pub(crate) fn unit_names() -> impl Iterator<Item = &'static str> {
+    UNITS
+        .iter()
+        .flat_map(|unit| unit.symbols.iter().chain(unit.names))
+        .copied()
+}
+
+/// What kind of values a unit is for, dates and times of the day are displayed differently from other numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnitKind {
+    Measure,
+    /// The value is the number of days since 1970-01-01
+    Date,
+    /// The value is the number of seconds since midnight
+    Clock,
+}
+
+/// A unit of a compound unit like ```km/h```, with its exponent
+#[derive(Debug, Clone, PartialEq)]
+struct UnitFactor {
+    /// How the unit was written, like ```km``` or ```hours```
+    name: String,
+    /// The size of the unit in SI units, including its prefix
+    scale: Literal,
+    /// Currencies are divided by their exchange rate instead, so the conversions stay exact
+    divisor: Option<Literal>,
+    dimension: Dimension,
+    exponent: i32,
+    kind: UnitKind,
+}
+
+/// A product of units with exponents like ```kg*m/s^2```, numbers without a unit have an empty one
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Unit {
+    factors: Vec<UnitFactor>,
+}
+
+impl Unit {
+    /// Look up a unit by its name, like ```km```, ```ft``` or ```hours```
+    pub(crate) fn parse(name: &str) -> Option<Unit> {
+        let (definition, prefix) = UNITS
+            .iter()
+            .find(|unit| unit.symbols.contains(&name) || unit.names.contains(&name))
+            .map(|unit| (unit, None))
+            .or_else(|| {
+                PREFIXES.iter().find_map(|(prefix, factor)| {
+                    let symbol = name.strip_prefix(prefix)?;
+
+                    UNITS
+                        .iter()
+                        .find(|unit| unit.prefixable && unit.symbols.contains(&symbol))
+                        .map(|unit| (unit, Some(*factor)))
+                })
+            })?;
+
+        //The scales and prefixes are valid numbers
+        let mut scale = Literal::parse(definition.scale).unwrap();
+
+        if let Some(factor) = prefix {
+            let factor = Literal::parse(factor).unwrap();
+
+            scale = Literal {
+                float: scale.float * factor.float,
+                exact: scale.exact * factor.exact,
+            };
+        }
+
+        Some(Unit {
+            factors: vec![UnitFactor {
+                name: name.to_string(),
+                scale,
+                divisor: None,
+                dimension: definition.dimension,
+                exponent: 1,
+                kind: UnitKind::Measure,
+            }],
+        })
+    }
+
+    /// A currency like ```EUR```, the rate is how much of it one unit of the base currency of the exchange rates is worth
+    pub(crate) fn currency(code: &str, rate: &Literal) -> Unit {
+        Unit {
+            factors: vec![UnitFactor {
+                name: code.to_string(),
+                //1 is a valid number
+                scale: Literal::parse("1").unwrap(),
+                divisor: Some(rate.clone()),
+                dimension: MONEY,
+                exponent: 1,
+                kind: UnitKind::Measure,
+            }],
+        }
+    }
+
+    /// The unit of dates, their value is the number of days since 1970-01-01 so adding ```45 days``` moves them
+    pub(crate) fn date() -> Unit {
+        Unit::special("date", "86400", UnitKind::Date)
+    }
+
+    /// The unit of times of the day like ```17:30```, their value is the number of seconds since midnight
+    pub(crate) fn clock() -> Unit {
+        Unit::special("s", "1", UnitKind::Clock)
+    }
+
+    /// The unit of the difference of two dates
+    pub(crate) fn days() -> Unit {
+        //days is in the table
+        Unit::parse("days").unwrap()
+    }
+
+    fn special(name: &str, scale: &str, kind: UnitKind) -> Unit {
+        Unit {
+            factors: vec![UnitFactor {
+                name: name.to_string(),
+                //The scales are valid numbers
+                scale: Literal::parse(scale).unwrap(),
+                divisor: None,
+                dimension: TIME,
+                exponent: 1,
+                kind,
+            }],
+        }
+    }
+
+    /// Dates and times of the day are only displayed as such if they havent been combined with other units
+    pub(crate) fn kind(&self) -> UnitKind {
+        match self.factors.as_slice() {
+            [factor] if factor.exponent == 1 => factor.kind,
+            _ => UnitKind::Measure,
+        }
+    }
+
+    pub(crate) fn is_date(&self) -> bool {
+        self.kind() == UnitKind::Date
+    }
+
+    /// Whether this is the unit of a plain number
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    pub(crate) fn dimension(&self) -> Dimension {
+        self.factors
+            .iter()
+            .fold(DIMENSIONLESS, |dimension, factor| {
+                dimension.mul(factor.dimension.pow(factor.exponent))
+            })
+    }
+
+    /// The nth root of the unit: ```sqrt(m^2)``` is ```m```, None if an exponent isnt divisible by n
+    pub(crate) fn root(&self, n: i32) -> Option<Unit> {
+        let factors = self
+            .factors
+            .iter()
+            .map(|factor| {
+                (factor.exponent % n == 0).then(|| UnitFactor {
+                    exponent: factor.exponent / n,
+                    ..factor.clone()
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Unit { factors })
+    }
+
+    fn inverse(self) -> Unit {
+        Unit {
+            factors: self
+                .factors
+                .into_iter()
+                .map(|factor| UnitFactor {
+                    exponent: -factor.exponent,
+                    ..factor
+                })
+                .collect(),
+        }
+    }
+
+    /// The size of the unit in SI units as a fraction, so a conversion only has to divide once
+    fn scale(&self, mode: NumberMode, context: &Context) -> (Number, Number) {
+        let mut numerator = Number::from_integer(1, mode);
+        let mut denominator = Number::from_integer(1, mode);
+
+        for factor in &self.factors {
+            let power = |literal: &Literal| {
+                Number::from_literal(literal, mode).pow(
+                    Number::from_integer(factor.exponent.abs().into(), mode),
+                    context,
+                )
+            };
+
+            let scale = power(&factor.scale);
+
+            let divisor = match &factor.divisor {
+                Some(divisor) => power(divisor),
+                None => Number::from_integer(1, mode),
+            };
+
+            //A negative exponent swaps the numerator and the denominator
+            if factor.exponent > 0 {
+                numerator = numerator.mul(scale, context);
+                denominator = denominator.mul(divisor, context);
+            } else {
+                numerator = numerator.mul(divisor, context);
+                denominator = denominator.mul(scale, context);
+            }
+        }
+
+        (numerator, denominator)
+    }
+}
+
+impl Display for Unit {
+    /// The units with a negative exponent are written after a /, like ```kg*m/s^2```
+    /// If there are only negative exponents they are written out instead: ```0.5 s^-1```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |factors: Vec<(&str, i32)>| {
+            factors
+                .iter()
+                .map(|(name, exponent)| match exponent {
+                    1 => name.to_string(),
+                    exponent => format!("{name}^{exponent}"),
+                })
+                .collect::<Vec<String>>()
+                .join("*")
+        };
+
+        let (numerator, denominator): (Vec<_>, Vec<_>) = self
+            .factors
+            .iter()
+            .map(|factor| (factor.name.as_str(), factor.exponent))
+            .partition(|(_, exponent)| *exponent > 0);
+
+        if numerator.is_empty() || denominator.is_empty() {
+            return f.write_str(&join(denominator.into_iter().chain(numerator).collect()));
+        }
+
+        let denominator_count = denominator.len();
+
+        let denominator = join(
+            denominator
+                .into_iter()
+                .map(|(name, exponent)| (name, -exponent))
+                .collect(),
+        );
+
+        if denominator_count == 1 {
+            write!(f, "{}/{denominator}", join(numerator))
+        } else {
+            write!(f, "{}/({denominator})", join(numerator))
+        }
+    }
+}
+
+/// A number with a unit, this is what expressions evaluate to
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Quantity {
+    pub number: Number,
+    pub unit: Unit,
+}
+
+impl From<Number> for Quantity {
+    fn from(number: Number) -> Self {
+        Quantity::new(number, Unit::default())
+    }
+}
+
+impl Quantity {
+    pub fn new(number: Number, unit: Unit) -> Self {
+        Self { number, unit }
+    }
+
+    /// Convert the quantity to another unit, both units have to have the same dimension
+    pub fn convert(self, unit: &Unit, mode: NumberMode, context: &Context) -> Quantity {
+        if &self.unit == unit {
+            return self;
+        }
+
+        let (from_numerator, from_denominator) = self.unit.scale(mode, context);
+        let (to_numerator, to_denominator) = unit.scale(mode, context);
+
+        let number = self
+            .number
+            .mul(from_numerator, context)
+            .mul(to_denominator, context)
+            .div(from_denominator.mul(to_numerator, context), context);
+
+        Quantity::new(number, unit.clone())
+    }
+
+    pub fn mul(self, rhs: Quantity, mode: NumberMode, context: &Context) -> Quantity {
+        Quantity::new(self.number.mul(rhs.number, context), self.unit)
+            .multiply_unit(rhs.unit, mode, context)
+    }
+
+    pub fn div(self, rhs: Quantity, mode: NumberMode, context: &Context) -> Quantity {
+        Quantity::new(self.number.div(rhs.number, context), self.unit).multiply_unit(
+            rhs.unit.inverse(),
+            mode,
+            context,
+        )
+    }
+
+    /// The exponents of the unit are multiplied too, None if they dont stay integers like in ```(2 m)^0.5```
+    pub fn pow(self, exponent: Number, context: &Context) -> Option<Quantity> {
+        let power = exponent.to_f64();
+
+        let factors = self
+            .unit
+            .factors
+            .into_iter()
+            .map(|factor| {
+                let exponent = factor.exponent as f64 * power;
+
+                (exponent.fract() == 0. && exponent.abs() <= i32::MAX as f64).then_some(
+                    UnitFactor {
+                        exponent: exponent as i32,
+                        ..factor
+                    },
+                )
+            })
+            .filter(|factor| !matches!(factor, Some(UnitFactor { exponent: 0, .. })))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Quantity::new(
+            self.number.pow(exponent, context),
+            Unit { factors },
+        ))
+    }
+
+    /// Multiply the unit of the quantity with another unit
+    /// Units with the same dimension are converted to the one which came first, so ```m*cm``` is ```m^2```
+    fn multiply_unit(self, unit: Unit, mode: NumberMode, context: &Context) -> Quantity {
+        let both_have_units = !self.unit.is_empty() && !unit.is_empty();
+
+        let Quantity {
+            mut number,
+            unit: Unit { mut factors },
+        } = self;
+
+        for factor in unit.factors {
+            let existing = factors
+                .iter()
+                .position(|existing| existing.name == factor.name)
+                .or_else(|| {
+                    factors
+                        .iter()
+                        .position(|existing| existing.dimension == factor.dimension)
+                });
+
+            let Some(existing) = existing else {
+                factors.push(factor);
+
+                continue;
+            };
+
+            let existing = &mut factors[existing];
+
+            if existing.name != factor.name {
+                let target = Unit {
+                    factors: vec![UnitFactor {
+                        exponent: factor.exponent,
+                        ..existing.clone()
+                    }],
+                };
+
+                number = Quantity::new(
+                    number,
+                    Unit {
+                        factors: vec![factor.clone()],
+                    },
+                )
+                .convert(&target, mode, context)
+                .number;
+            }
+
+            existing.exponent += factor.exponent;
+        }
+
+        factors.retain(|factor| factor.exponent != 0);
+
+        let quantity = Quantity::new(number, Unit { factors });
+
+        //Units which cancel each other out like J/(N*m) result in a plain number
+        if both_have_units
+            && !quantity.unit.is_empty()
+            && quantity.unit.dimension() == DIMENSIONLESS
+        {
+            return quantity.convert(&Unit::default(), mode, context);
+        }
+
+        quantity
+    }
+}
+
+impl Neg for Quantity {
+    type Output = Quantity;
+
+    fn neg(self) -> Self::Output {
+        Quantity::new(-self.number, self.unit)
+    }
+}
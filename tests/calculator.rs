@@ -0,0 +1,1076 @@
+use calculator_recode::{
+    Answer, Calculator, CalculatorErrorType, ExchangeRateError, ExchangeRates, NumberMode,
+    PercentMode, RemainderMode, RoundingMode, SyntaxKind, Warning,
+};
+
+fn calculate(input: &str) -> f64 {
+    Calculator::new().calculate(input).unwrap()
+}
+
+#[test]
+fn unary_minus_and_plus() {
+    assert_eq!(calculate("-5 + 3"), -2.);
+    assert_eq!(calculate("2 * -4"), -8.);
+    assert_eq!(calculate("(-(3+1))^2"), 16.);
+    assert_eq!(calculate("--5"), 5.);
+    assert_eq!(calculate("+5 - +3"), 2.);
+}
+
+#[test]
+fn operator_precedence_and_brackets() {
+    assert_eq!(calculate("2 + 3 * 4"), 14.);
+    assert_eq!(calculate("(2 + 3) * 4"), 20.);
+    assert_eq!(calculate("10 - 4 - 3"), 3.);
+    assert_eq!(calculate("8 / 4 / 2"), 1.);
+    assert_eq!(calculate("2 * (3 + (4 - 1)) / 3"), 4.);
+    assert!(Calculator::new().calculate("(1 + 2").is_err());
+    assert!(Calculator::new().calculate("1 + 2)").is_err());
+    assert!(Calculator::new().calculate("1 +").is_err());
+}
+
+#[test]
+fn power_is_right_associative() {
+    assert_eq!(calculate("2^3^2"), 512.);
+    assert_eq!(calculate("(2^3)^2"), 64.);
+    assert_eq!(calculate("2^2^3^0"), 4.);
+}
+
+#[test]
+fn power_binds_tighter_than_unary_minus() {
+    assert_eq!(calculate("-2^2"), -4.);
+    assert_eq!(calculate("(-2)^2"), 4.);
+}
+
+#[test]
+fn power_with_negative_exponent() {
+    assert_eq!(calculate("2^-1"), 0.5);
+    assert_eq!(calculate("2^-1^2"), 0.5);
+}
+
+#[test]
+fn variables_are_kept_between_calculations() {
+    let mut calculator = Calculator::new();
+
+    assert_eq!(calculator.calculate("x = 3.5").unwrap(), 3.5);
+    assert_eq!(calculator.calculate("rate = 0.5").unwrap(), 0.5);
+    assert_eq!(calculator.calculate("2x(1 + rate)^2").unwrap(), 15.75);
+    assert!(calculator.calculate("y + 1").is_err());
+}
+
+#[test]
+fn builtin_functions() {
+    assert_eq!(calculate("sqrt(16)"), 4.);
+    assert_eq!(calculate("log(1000, 10)"), 3.);
+    assert_eq!(calculate("max(3, 7, 2) - min(3, 7, 2)"), 5.);
+    assert_eq!(calculate("2abs(-3)^2"), 18.);
+    assert!(Calculator::new().calculate("sqrt(1, 2)").is_err());
+}
+
+#[test]
+fn constants_with_implicit_multiplication() {
+    assert_eq!(calculate("2pi"), std::f64::consts::TAU);
+    assert_eq!(calculate("3e"), 3. * std::f64::consts::E);
+    assert!(Calculator::new().calculate("pi = 3").is_err());
+}
+
+#[test]
+#[cfg(feature = "physical-constants")]
+fn physical_constants_dont_hide_units() {
+    assert_eq!(calculate("c"), 299_792_458.);
+    assert_eq!(calculate("2 g0"), 19.6133);
+    assert_eq!(calculate("planck"), 6.626_070_15e-34);
+    //g and h are still grams and hours
+    assert_eq!(calculate("1000 g to kg"), 1.);
+    assert_eq!(calculate("2 h to min"), 120.);
+}
+
+#[test]
+fn user_defined_functions() {
+    let mut calculator = Calculator::new();
+
+    assert_eq!(
+        calculator.execute("f(x, y) = x^2 + y").unwrap(),
+        Answer::FunctionDefinition("f".to_string())
+    );
+    assert_eq!(calculator.calculate("f(3, 4)").unwrap(), 13.);
+    assert!(calculator.calculate("f(3)").is_err());
+
+    //Endless recursion is stopped
+    calculator.execute("g(x) = g(x) + 1").unwrap();
+    assert_eq!(
+        *calculator.calculate("g(1)").unwrap_err().error_type(),
+        CalculatorErrorType::RecursionLimit(64)
+    );
+
+    //Every parameter needs its own name
+    let error = calculator.execute("k(x, y, x) = x").unwrap_err();
+    assert_eq!(
+        *error.error_type(),
+        CalculatorErrorType::DuplicateParameter("x".to_string())
+    );
+    assert_eq!(error.span(), 8..9);
+    assert!(calculator.calculate("k(1, 2, 3)").is_err());
+
+    //calculate doesnt accept definitions
+    assert!(calculator.calculate("h(x) = x").is_err());
+    assert!(calculator.calculate("h(1)").is_err());
+
+    //Functions can call functions which are defined later, also each other
+    calculator.execute("a(x) = b(x) + 1").unwrap();
+    assert_eq!(
+        *calculator.calculate("a(5)").unwrap_err().error_type(),
+        CalculatorErrorType::UnknownFunction("b".to_string())
+    );
+    assert!(calculator.check("c(x) = later(x)").is_empty());
+    calculator.execute("b(x) = 2x").unwrap();
+    assert_eq!(calculator.calculate("a(5)").unwrap(), 11.);
+
+    calculator.execute("p(x) = q(x) + 1").unwrap();
+    calculator.execute("q(x) = p(x) + 1").unwrap();
+    assert_eq!(
+        *calculator.calculate("p(1)").unwrap_err().error_type(),
+        CalculatorErrorType::RecursionLimit(64)
+    );
+}
+
+#[test]
+fn modulo() {
+    assert_eq!(calculate("10 % 3"), 1.);
+    assert_eq!(calculate("2 + 7 % 3 * 2"), 4.);
+    assert_eq!(calculate("-7 % 3"), -1.);
+    assert!(Calculator::new().calculate("5 % 0").is_err());
+
+    let mut calculator = Calculator::new();
+    calculator.set_remainder_mode(RemainderMode::Euclidean);
+
+    assert_eq!(calculator.calculate("-7 % 3").unwrap(), 2.);
+}
+
+#[test]
+fn numbers_in_a_row_arent_multiplied() {
+    let mut calculator = Calculator::new();
+
+    for input in ["1 000", "1 2 + 3", "(1 + 2) 3 4"] {
+        assert_eq!(
+            *calculator.calculate(input).unwrap_err().error_type(),
+            CalculatorErrorType::UnexpectedToken
+        );
+    }
+
+    //Anything else next to a number is still multiplied
+    assert_eq!(calculator.calculate("2 (3) 4").unwrap(), 24.);
+}
+
+#[test]
+fn percentages() {
+    let mut calculator = Calculator::new();
+    calculator.set_percent_mode(PercentMode::Percentage);
+
+    assert_eq!(calculator.calculate("200 + 10%").unwrap(), 220.);
+    assert_eq!(calculator.calculate("200 - 10%").unwrap(), 180.);
+    assert_eq!(calculator.calculate("200 * 10%").unwrap(), 20.);
+    assert_eq!(calculator.calculate("200 / 10%").unwrap(), 2000.);
+    assert_eq!(calculator.calculate("50% of 80").unwrap(), 40.);
+    assert_eq!(calculator.calculate("25%").unwrap(), 0.25);
+    assert_eq!(calculator.calculate("(100 + 20%) - 50%").unwrap(), 60.);
+    assert_eq!(calculator.calculate("200 + -10%").unwrap(), 180.);
+    assert_eq!(calculator.calculate("200 - -10%").unwrap(), 220.);
+
+    //In the default mode % is still the remainder
+    assert_eq!(calculate("200 % 30"), 20.);
+}
+
+#[test]
+fn factorials() {
+    assert_eq!(calculate("5!"), 120.);
+    assert_eq!(calculate("7!!"), 105.);
+    assert_eq!(calculate("3!!"), 3.);
+    assert_eq!(calculate("-3!"), -6.);
+    assert_eq!(calculate("2^3!"), 64.);
+    assert!((calculate("0.5!") - std::f64::consts::PI.sqrt() / 2.).abs() < 1e-12);
+
+    //Overflow and undefined values are errors instead of inf and NaN
+    assert!(Calculator::new().calculate("171!").is_err());
+    assert!(Calculator::new().calculate("(-1)!").is_err());
+}
+
+#[test]
+fn scientific_notation() {
+    assert_eq!(calculate("6.022e23"), 6.022e23);
+    assert_eq!(calculate("1E-9"), 1e-9);
+    assert_eq!(calculate("2.5e+3"), 2500.);
+
+    //The exponent ends where the digits end, so this is 2000 * x
+    let mut calculator = Calculator::new();
+    calculator.calculate("x = 2").unwrap();
+
+    assert_eq!(calculator.calculate("2e3x").unwrap(), 4000.);
+}
+
+#[test]
+fn e_after_a_number_is_only_an_exponent_when_digits_follow() {
+    use std::f64::consts::E;
+
+    //2e is 2 * e
+    assert_eq!(calculate("2e"), 2. * E);
+    //A sign right after the e belongs to the exponent, but a space ends the number
+    assert_eq!(calculate("2e+3"), 2000.);
+    assert_eq!(calculate("2e + 3"), 2. * E + 3.);
+    //e followed by letters is an identifier
+    assert_eq!(calculate("2exp(0)"), 2.);
+    //There can only be one exponent
+    assert!(Calculator::new().calculate("1e5e3").is_err());
+}
+
+#[test]
+fn radix_literals_and_display_bases() {
+    assert_eq!(calculate("0xFF + 0b1010"), 265.);
+    assert_eq!(calculate("0o777"), 511.);
+    //The e of a hexadecimal literal is a digit, not an exponent
+    assert_eq!(calculate("0xE+1"), 15.);
+    assert_eq!(calculate("0x1e-1"), 29.);
+
+    //A prefix without valid digits is an invalid literal, not a multiplication with x or b
+    for (input, literal) in [("0x", "0x"), ("0b2", "0b2"), ("0o9 + 1", "0o9")] {
+        let error = Calculator::new().calculate(input).unwrap_err();
+
+        assert_eq!(
+            *error.error_type(),
+            CalculatorErrorType::InvalidNumberLiteral
+        );
+        assert_eq!(&input[error.span()], literal);
+    }
+
+    let mut calculator = Calculator::new();
+
+    assert_eq!(calculator.execute("hex(255)").unwrap().to_string(), "0xFF");
+    assert_eq!(calculator.execute("bin(10)").unwrap().to_string(), "0b1010");
+    assert_eq!(
+        calculator.execute("base(1295, 36)").unwrap().to_string(),
+        "ZZ (base 36)"
+    );
+    assert!(calculator.execute("base(1, 37)").is_err());
+    //Only the outermost call decides the base, nested ones still check theirs
+    assert_eq!(
+        calculator
+            .execute("base(base(255, 2), 16)")
+            .unwrap()
+            .to_string(),
+        "0xFF"
+    );
+    assert_eq!(
+        *calculator
+            .execute("1 + base(1, 37)")
+            .unwrap_err()
+            .error_type(),
+        CalculatorErrorType::InvalidBase
+    );
+
+    //The base is taken from the calculation of the call, it can come from anywhere
+    calculator.execute("twice(x) = 2x").unwrap();
+
+    assert_eq!(
+        calculator
+            .execute("base(255, twice(8))")
+            .unwrap()
+            .to_string(),
+        "0xFF"
+    );
+
+    calculator.set_display_base(8).unwrap();
+
+    assert_eq!(calculator.execute("-8.5").unwrap().to_string(), "-0o10.4");
+
+    //Invalid settings are errors and dont change anything
+    assert_eq!(
+        *calculator.set_display_base(37).unwrap_err().error_type(),
+        CalculatorErrorType::InvalidBase
+    );
+    assert_eq!(calculator.execute("8").unwrap().to_string(), "0o10");
+}
+
+#[test]
+fn bitwise_operators() {
+    assert_eq!(calculate("12 & 10"), 8.);
+    assert_eq!(calculate("12 | 3"), 15.);
+    assert_eq!(calculate("12 xor 10"), 6.);
+    assert_eq!(calculate("~5"), -6.);
+    assert_eq!(calculate("1 << 4 | 256 >> 4"), 16.);
+    //Arithmetic binds tighter than the bitwise operators
+    assert_eq!(calculate("1 + 2 & 3"), 3.);
+
+    assert!(Calculator::new().calculate("1.5 & 1").is_err());
+    assert_eq!(calculate("3 << 62"), 13835058055282163712.);
+    assert_eq!(calculate("-1 >> 100"), -1.);
+    //Floats this large have lost their last bits
+    assert_eq!(
+        *Calculator::new()
+            .calculate("0xFFFFFFFFFFFFFFFF & 1")
+            .unwrap_err()
+            .error_type(),
+        CalculatorErrorType::Overflow
+    );
+    assert_eq!(
+        *Calculator::new()
+            .calculate("1 << -1")
+            .unwrap_err()
+            .error_type(),
+        CalculatorErrorType::DomainError
+    );
+
+    let mut calculator = Calculator::new();
+    calculator.set_number_mode(NumberMode::Integer);
+
+    let mut execute = |input: &str| calculator.execute(input).map(|result| result.to_string());
+    assert_eq!(execute("3 << 62").unwrap(), "13835058055282163712");
+    assert_eq!(execute("0xFFFFFFFFFFFFFFFF & 1").unwrap(), "1");
+    assert_eq!(
+        execute("2^100 | 1").unwrap(),
+        "1267650600228229401496703205377"
+    );
+    assert_eq!(execute("~(2^64)").unwrap(), "-18446744073709551617");
+    assert_eq!(
+        *execute("1 << 10^12").unwrap_err().error_type(),
+        CalculatorErrorType::Overflow
+    );
+}
+
+#[test]
+fn decimal_mode() {
+    let mut calculator = Calculator::new();
+
+    assert_eq!(
+        calculator.execute("0.1 + 0.2").unwrap().to_string(),
+        "0.30000000000000004"
+    );
+
+    calculator.set_number_mode(NumberMode::Decimal);
+
+    let mut execute = |input: &str| calculator.execute(input).unwrap().to_string();
+
+    assert_eq!(execute("0.1 + 0.2"), "0.3");
+    assert_eq!(execute("1.10 * 3"), "3.30");
+    //Zeros which are only left over from rounding arent shown
+    assert_eq!(execute("2/3*3"), "2");
+    assert_eq!(execute("2/3*300"), "200");
+    assert_eq!(execute("10 / 4"), "2.5");
+    assert_eq!(execute("1 / 3"), "0.3333333333333333333333333333");
+    assert_eq!(execute("2^64"), "18446744073709551616");
+    assert_eq!(execute("-7 % 3"), "-1");
+    assert_eq!(execute("sqrt(16)"), "4");
+    //Only the significant digits are limited
+    assert_eq!(execute("0.1^30"), "0.000000000000000000000000000001");
+    //Very large and small decimals are written in scientific notation
+    assert_eq!(execute("1e100000000"), "1e100000000");
+    assert_eq!(execute("-0.1^200"), "-1e-200");
+    assert_eq!(
+        execute("2^10000000 - 1"),
+        "9.049817306360800301396402668e3010299"
+    );
+    assert_eq!(execute("1e100000000 - 1e100000000"), "0");
+    assert_eq!(execute("2^1000000000000"), "inf");
+    //Odd roots of negative decimals are real like in the rational mode
+    assert_eq!(execute("(-8)^(1/3)"), "-2");
+    assert_eq!(execute("(-8)^(2/3)"), "4");
+    assert_eq!(execute("(-0.001)^(1/3)"), "-0.1");
+    assert_eq!(execute("(-2)^(1/3)"), (-2f64.cbrt()).to_string());
+
+    calculator.set_decimal_precision(5).unwrap();
+    calculator.set_decimal_rounding(RoundingMode::Down);
+
+    assert_eq!(calculator.execute("2 / 3").unwrap().to_string(), "0.66666");
+    assert_eq!(
+        *calculator
+            .set_decimal_precision(0)
+            .unwrap_err()
+            .error_type(),
+        CalculatorErrorType::InvalidPrecision
+    );
+    assert_eq!(calculator.calculate("0.1 + 0.2").unwrap(), 0.3);
+}
+
+#[test]
+fn rational_mode() {
+    let mut calculator = Calculator::new();
+
+    calculator.set_number_mode(NumberMode::Rational);
+
+    let mut execute = |input: &str| calculator.execute(input).unwrap();
+
+    assert_eq!(execute("1/3 + 1/6").to_string(), "1/2");
+    assert_eq!(execute("0.25 * 2").to_string(), "1/2");
+    assert_eq!(execute("(2/3)^-2").to_string(), "9/4");
+    assert_eq!(execute("sqrt(4/9)").to_string(), "2/3");
+    assert_eq!(execute("(-8)^(1/3)").to_string(), "-2");
+    assert!(execute("1/3 + 1/6").warnings().is_empty());
+
+    let irrational = execute("sqrt(2) + 1");
+
+    assert_eq!(irrational.to_string(), "2.414213562373095");
+    assert_eq!(
+        irrational.warnings(),
+        [Warning::FloatFallback("sqrt(2)".to_string())]
+    );
+
+    //Exponents too large for exact fractions fall back to floats, instead of being cut off or taking forever
+    for (input, result) in [("1e4294967297", "inf"), ("1e100000000", "inf")] {
+        let answer = execute(input);
+
+        assert_eq!(answer.to_string(), result);
+        assert_eq!(
+            answer.warnings(),
+            [
+                Warning::Overflow(input.to_string()),
+                Warning::FloatFallback(input.to_string())
+            ]
+        );
+    }
+
+    let answer = execute("1e-4294967297");
+
+    assert_eq!(answer.to_string(), "0");
+    assert_eq!(
+        answer.warnings(),
+        [Warning::FloatFallback("1e-4294967297".to_string())]
+    );
+}
+
+#[test]
+fn integer_mode() {
+    let mut calculator = Calculator::new();
+
+    calculator.set_number_mode(NumberMode::Integer);
+
+    let mut execute = |input: &str| calculator.execute(input).unwrap().to_string();
+
+    assert_eq!(
+        execute("2^200"),
+        "1606938044258990275541962092341162602522202993782792835301376"
+    );
+    assert_eq!(execute("30!"), "265252859812191058636308480000000");
+    assert_eq!(execute("10000!").len(), 35660);
+    assert_eq!(execute("gcd(12, 18, 30)"), "6");
+    assert_eq!(execute("lcm(4, 6)"), "12");
+    assert_eq!(execute("powmod(2, 1000, 1000000007)"), "688423210");
+    assert_eq!(execute("hex(2^100)"), "0x10000000000000000000000000");
+    //Divisions have to be exact
+    assert_eq!(execute("8 / 2"), "4");
+    assert_eq!(execute("2^100 / 2^99"), "2");
+    assert_eq!(execute("1.5e20"), "150000000000000000000");
+    //Exponents too large for exact integers fall back to floats
+    assert_eq!(execute("1e4294967297"), "inf");
+    assert_eq!(execute("1e-4294967297"), "0");
+
+    assert_eq!(calculate("gcd(12, 18)"), 6.);
+
+    //Invalid arguments are errors in every number mode, they point at the argument
+    for (input, error_type, span) in [
+        ("powmod(2, -1, 7)", CalculatorErrorType::DomainError, 10..12),
+        (
+            "powmod(3, 4, 0)",
+            CalculatorErrorType::RemainderByZero,
+            13..14,
+        ),
+        ("gcd(12, 2.5)", CalculatorErrorType::DomainError, 8..11),
+        ("lcm(7/2, 4)", CalculatorErrorType::DomainError, 4..7),
+    ] {
+        for calculator in [&mut Calculator::new(), &mut calculator] {
+            let error = calculator.execute(input).unwrap_err();
+
+            assert_eq!(*error.error_type(), error_type);
+            assert_eq!(error.span(), span);
+        }
+    }
+
+    //Integers which dont divide exactly have no integer result
+    let error = calculator.execute("1 + 7 / 2").unwrap_err();
+
+    assert_eq!(*error.error_type(), CalculatorErrorType::DomainError);
+    assert_eq!(error.span(), 4..9);
+
+    //Factorials which would take too long to multiply out arent silently turned into floats
+    assert_eq!(
+        *calculator.execute("1000000!").unwrap_err().error_type(),
+        CalculatorErrorType::ExactResultTooLarge
+    );
+}
+
+#[test]
+fn complex_mode() {
+    //Without complex numbers i is an unknown variable
+    assert!(Calculator::new().calculate("sqrt(-1) + i").is_err());
+
+    let mut calculator = Calculator::new();
+
+    calculator.set_number_mode(NumberMode::Complex);
+
+    let mut execute = |input: &str| calculator.execute(input).unwrap().to_string();
+
+    assert_eq!(execute("sqrt(-4)"), "2i");
+    assert_eq!(execute("i^2"), "-1");
+    assert_eq!(execute("(3 + 4i)(1 - 2i)"), "11 - 2i");
+    assert_eq!(execute("abs(3 + 4i)"), "5");
+    assert_eq!(execute("conj(2 + i)"), "2 - i");
+    assert_eq!(execute("re(2 - 5i) + im(2 - 5i)"), "-3");
+    assert_eq!(execute("ln(-1)"), format!("{}i", std::f64::consts::PI));
+    assert_eq!(execute("(5 + 0i) % 3"), "2");
+    assert_eq!(execute("hex(i^2)"), "-0x1");
+    assert_eq!(execute("max(i^2, 2 + 0i)"), "2");
+
+    assert!(calculator.execute("i = 3").is_err());
+    //The remainder, other bases and comparisons are only defined for real numbers
+    for input in [
+        "(1 + i) % 2",
+        "5 % (2 - i)",
+        "hex(i)",
+        "base(1 + i, 3)",
+        "max(i, 1)",
+        "max(1, i)",
+        "min(2, 3 - i)",
+    ] {
+        assert_eq!(
+            *calculator.execute(input).unwrap_err().error_type(),
+            CalculatorErrorType::DomainError
+        );
+    }
+}
+
+#[test]
+fn units() {
+    let mut calculator = Calculator::new();
+
+    let mut execute = |input: &str| calculator.execute(input).unwrap().to_string();
+
+    assert_eq!(execute("5 km / 20 min in km/h"), "15 km/h");
+    assert_eq!(execute("5 m * 20 cm"), "1 m^2");
+    assert_eq!(execute("sqrt(16 m^2)"), "4 m");
+    assert_eq!(execute("9.81 m/s^2 * 70 kg to N"), "686.7 N");
+    assert_eq!(execute("1 kWh in J"), "3600000 J");
+
+    assert_eq!(calculate("1 mi in km"), 1.609344);
+    assert_eq!(calculate("sin(90 deg)"), 1.);
+    //Units which cancel each other out give a plain number
+    assert_eq!(calculate("1 km / 20 m"), 50.);
+
+    assert!(Calculator::new().calculate("3 m + 2 s").is_err());
+    assert!(Calculator::new().calculate("2 km in s").is_err());
+    assert!(Calculator::new().calculate("sin(5 m)").is_err());
+
+    //Conversions are exact with exact numbers
+    let mut calculator = Calculator::new();
+    calculator.set_number_mode(NumberMode::Rational);
+
+    assert_eq!(
+        calculator.execute("3 ft + 20 cm").unwrap().to_string(),
+        "1393/381 ft"
+    );
+
+    //Variables hide units with the same name
+    calculator.calculate("m = 3").unwrap();
+
+    assert_eq!(calculator.calculate("2m").unwrap(), 6.);
+
+    //Operators cant be used as names, they wouldnt be read back as the name
+    for (input, name) in [
+        ("in = 3", "in"),
+        ("to = 3", "to"),
+        ("xor = 3", "xor"),
+        ("of = 3", "of"),
+        ("f(in) = in + 1", "in"),
+    ] {
+        assert_eq!(
+            *calculator.calculate(input).unwrap_err().error_type(),
+            CalculatorErrorType::ReservedName(name.to_string())
+        );
+    }
+
+    assert_eq!(calculator.calculate("2000 cm in km").unwrap(), 0.02);
+}
+
+#[test]
+fn currencies() {
+    let mut calculator = Calculator::new();
+
+    //Without a rate table the currencies are known, but they cant be used
+    assert!(calculator.calculate("120 USD").is_err());
+
+    let csv = "currency,rate\nUSD,1\nEUR,0.8\nGBP,0.75\n";
+
+    calculator.set_exchange_rates(ExchangeRates::from_csv(csv.as_bytes()).unwrap());
+    calculator.set_number_mode(NumberMode::Decimal);
+
+    let mut execute = |input: &str| calculator.execute(input).unwrap().to_string();
+
+    assert_eq!(execute("120 USD + 80 EUR in GBP"), "165 GBP");
+    assert_eq!(execute("100 EUR / 4 h"), "25 EUR/h");
+    assert!(calculator.calculate("5 JPY").is_err());
+    assert!(calculator.calculate("5 USD + 3 m").is_err());
+
+    let json = r#"{"base": "EUR", "rates": {"USD": 1.25, "JPY": "160"}}"#;
+
+    calculator.set_exchange_rates(ExchangeRates::from_json(json.as_bytes()).unwrap());
+
+    assert_eq!(calculator.calculate("500 JPY in USD").unwrap(), 3.90625);
+    assert!(ExchangeRates::from_csv("currency,rate\nEUR,zero".as_bytes()).is_err());
+    //A header with one column doesnt make the rows with one column valid
+    assert!(matches!(
+        ExchangeRates::from_csv("currency\nUSD\n".as_bytes()),
+        Err(ExchangeRateError::MissingColumns(2))
+    ));
+}
+
+#[test]
+fn dates_and_times() {
+    let mut calculator = Calculator::new();
+
+    let mut execute = |input: &str| calculator.execute(input).unwrap().to_string();
+
+    assert_eq!(execute("2026-10-18 + 45 days"), "2026-12-02");
+    assert_eq!(execute("3 weeks + 2026-10-18"), "2026-11-08");
+    assert_eq!(execute("2026-10-18 + 36 h"), "2026-10-19 12:00");
+    assert_eq!(execute("17:30 - 09:15"), "8:15");
+    assert_eq!(execute("(17:30 - 09:15) in minutes"), "495 minutes");
+    //Times of the day go around the clock
+    assert_eq!(execute("23:59 + 1 min"), "0:00");
+    assert_eq!(execute("17:30 + 17:30"), "11:00");
+    assert_eq!(execute("-17:30"), "6:30");
+    assert_eq!(execute("09:15 - 17:30"), "15:45");
+    assert_eq!(execute("17:30 * 2"), "11:00");
+    assert_eq!(execute("days_between(2026-01-01, 2026-12-25)"), "358 days");
+    assert_eq!(execute("2026-10-25 - 2026-10-18 in h"), "168 h");
+
+    assert!(Calculator::new().calculate("2026-10-18 * 2").is_err());
+    assert!(Calculator::new()
+        .calculate("2026-10-18 + 2026-10-19")
+        .is_err());
+    assert!(Calculator::new()
+        .calculate("days_between(2026-01-01, 5)")
+        .is_err());
+    //Dates outside of the years chrono supports
+    for input in [
+        "2026-10-18 + 100000000 days",
+        "2026-10-18 + 1e300 days",
+        "2026-10-18 - 1e300 days",
+    ] {
+        assert_eq!(
+            *Calculator::new().execute(input).unwrap_err().error_type(),
+            CalculatorErrorType::Overflow
+        );
+    }
+    assert_eq!(
+        *Calculator::new()
+            .execute("2026-10-18 + nan days")
+            .unwrap_err()
+            .error_type(),
+        CalculatorErrorType::DomainError
+    );
+    //Times of the day which cant be shown like a clock
+    for (input, error_type) in [
+        ("17:30 * nan", CalculatorErrorType::DomainError),
+        ("17:30 * 1e300", CalculatorErrorType::Overflow),
+        ("17:30 / 1e-300", CalculatorErrorType::Overflow),
+    ] {
+        assert_eq!(
+            *Calculator::new().execute(input).unwrap_err().error_type(),
+            error_type
+        );
+    }
+    //Times which dont exist
+    assert!(Calculator::new().calculate("25:00").is_err());
+    //Dates which dont exist are subtractions like before dates were added
+    assert_eq!(calculate("1234-56-78"), 1100.);
+    assert_eq!(calculate("2026-02-30"), 1994.);
+    assert_eq!(calculate("2000 - 12 - 12"), 1976.);
+    assert_eq!(calculate("2000-12 - 12"), 1976.);
+}
+
+#[test]
+fn error_spans_point_into_the_original_input() {
+    let span = |input: &str| {
+        let span = Calculator::new().calculate(input).unwrap_err().span();
+
+        input[span].to_string()
+    };
+
+    //Leading whitespace isnt stripped before the spans are made
+    assert_eq!(span("   12 / (3 - 3)"), "(3 - 3)");
+    assert_eq!(span("  2 * unknown + 1"), "unknown");
+    assert_eq!(span("1 + 1.2.3"), "1.2.3");
+    assert_eq!(span("3 m + 2 s"), "2 s");
+    assert_eq!(span("max(1, 2) + sqrt(1, 2)"), "sqrt(1, 2)");
+    assert_eq!(span("2026-10-18 * 2"), "2026-10-18 * 2");
+    assert_eq!(span("µ $ 2"), "$");
+    //Missing tokens at the end have an empty span
+    assert_eq!(span("2 + "), "");
+}
+
+#[test]
+fn diagnostics_report_every_problem() {
+    let mut calculator = Calculator::new();
+
+    let diagnostics = calculator.check("sqr(4 + (2 * y");
+
+    let messages: Vec<&str> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect();
+
+    assert_eq!(
+        messages,
+        [
+            "There is no function named `sqr`",
+            "The bracket is never closed",
+            "The bracket is never closed",
+            "There is no variable named `y`"
+        ]
+    );
+
+    assert_eq!(
+        diagnostics[0].suggestion.as_ref().unwrap().replacement,
+        "sqrt"
+    );
+    //Single letters are too short to guess what was meant
+    assert_eq!(diagnostics[3].suggestion, None);
+
+    //The missing ) belongs at the end
+    assert_eq!(diagnostics[1].labels[0].span, 3..4);
+    assert!(diagnostics[1].labels[0].primary);
+    assert_eq!(diagnostics[1].labels[1].span, 14..14);
+    assert!(!diagnostics[1].labels[1].primary);
+
+    assert_eq!(
+        diagnostics[0].render("sqr(4 + (2 * y"),
+        "error: There is no function named `sqr`\n  |\n1 | sqr(4 + (2 * y\n  | ^^^ unknown function\n  |\n  = help: did you mean `sqrt`?\n"
+    );
+
+    //Names in call position are unknown functions, the same as when calculating
+    for input in ["sqr(4)", "sin 0", "h(1)"] {
+        let error = calculator.calculate(input).unwrap_err();
+        let diagnostics = calculator.check(input);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, error.error_type().to_string());
+    }
+
+    assert!(matches!(
+        calculator.calculate("sqr(4)").unwrap_err().error_type(),
+        CalculatorErrorType::UnknownFunction(name) if name == "sqr"
+    ));
+    assert!(matches!(
+        calculator.calculate("sin 0").unwrap_err().error_type(),
+        CalculatorErrorType::MissingArgumentBrackets(name) if name == "sin"
+    ));
+    //Only functions are suggested for unknown functions
+    calculator.calculate("sqrtt = 2").unwrap();
+    assert_eq!(
+        calculator.check("sqrttt(4)")[0]
+            .suggestion
+            .as_ref()
+            .unwrap()
+            .replacement,
+        "sqrt"
+    );
+
+    //The parameters of a definition and assigned variables are known
+    assert!(calculator.check("f(x) = x^2 + f(x - 1)").is_empty());
+    assert!(calculator.check("rate = 3 km").is_empty());
+
+    //Errors found while calculating are diagnosed on their own
+    let error = calculator.calculate("1 / (3 - 3)").unwrap_err();
+    let diagnostics = calculator.diagnose(&error);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].labels[0].span, 4..11);
+
+    //The error comes first, followed by the other problems
+    for (input, error_message) in [
+        ("1/0 + y", "Tried to divide by 0"),
+        (
+            "sqrt(1/0, y)",
+            "`sqrt` takes 1 argument(s), but 2 were given",
+        ),
+    ] {
+        let error = calculator.calculate(input).unwrap_err();
+        let messages: Vec<String> = calculator
+            .diagnose(&error)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+
+        assert_eq!(messages, [error_message, "There is no variable named `y`"]);
+    }
+
+    //Syntax errors are only reported once
+    let error = calculator.calculate("(1 + 2").unwrap_err();
+    let diagnostics = calculator.diagnose(&error);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "The bracket is never closed");
+}
+
+#[test]
+fn errors_can_be_matched_on() {
+    let error_type = |input: &str| {
+        Calculator::new()
+            .calculate(input)
+            .unwrap_err()
+            .error_type()
+            .clone()
+    };
+
+    assert_eq!(error_type("1 / 0"), CalculatorErrorType::DivisionByZero);
+    assert_eq!(error_type("5 % 0"), CalculatorErrorType::RemainderByZero);
+    assert_eq!(
+        error_type("1.2.3"),
+        CalculatorErrorType::InvalidNumberLiteral
+    );
+    assert_eq!(error_type("1 $ 2"), CalculatorErrorType::InvalidCharacter);
+    assert_eq!(error_type("(1 + 2"), CalculatorErrorType::UnbalancedBracket);
+    assert_eq!(error_type("1 + 2)"), CalculatorErrorType::UnbalancedBracket);
+    assert_eq!(error_type("1 + * 2"), CalculatorErrorType::UnexpectedToken);
+    assert_eq!(error_type("2 +"), CalculatorErrorType::UnexpectedEndOfInput);
+    assert_eq!(error_type("171!"), CalculatorErrorType::Overflow);
+    assert_eq!(error_type("(-1)!"), CalculatorErrorType::DomainError);
+    assert_eq!(
+        error_type("y + 1"),
+        CalculatorErrorType::UnknownVariable("y".to_string())
+    );
+    assert_eq!(
+        error_type("h(1)"),
+        CalculatorErrorType::UnknownFunction("h".to_string())
+    );
+}
+
+#[test]
+fn recovering_parse_keeps_going_after_errors() {
+    let calculator = Calculator::new();
+
+    let input = "((42 * 3) - 18) / 2) + ((5 * ) - (12 / 2)) + 1.2.3 * (7 $ 3) + h(2";
+
+    let parsed = calculator.parse_with_recovery(input);
+
+    let messages: Vec<&str> = parsed
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect();
+
+    assert_eq!(
+        messages,
+        [
+            "The bracket has never been opened",
+            "This isnt expected here",
+            "The number couldnt be parsed, for example 1.2.3 or 25:00",
+            "The input contains a character which cant be calculated with",
+            "There is no function named `h`",
+            "The bracket is never closed"
+        ]
+    );
+
+    //The whole input is in the tree, with errors where the missing operand and the invalid number are
+    assert_eq!(parsed.tree.kind, SyntaxKind::Operator("+"));
+    assert_eq!(parsed.tree.span, 0..input.len());
+
+    let mut errors = Vec::new();
+    let mut nodes = vec![&parsed.tree];
+
+    while let Some(node) = nodes.pop() {
+        if let SyntaxKind::Error(error_type) = &node.kind {
+            errors.push((error_type.clone(), node.span.clone()));
+        }
+
+        nodes.extend(&node.children);
+    }
+
+    errors.sort_by_key(|(_, span)| span.start);
+
+    assert_eq!(
+        errors,
+        [
+            (CalculatorErrorType::UnexpectedToken, 29..30),
+            (CalculatorErrorType::InvalidNumberLiteral, 45..50)
+        ]
+    );
+
+    //A lone ) is reported once, even though the parser doesnt expect it either
+    let messages: Vec<String> = calculator
+        .check(")")
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect();
+
+    assert_eq!(messages, ["The bracket has never been opened"]);
+
+    //Without errors the tree is the same one the calculation uses
+    let parsed = calculator.parse_with_recovery("x = 2 km");
+
+    assert!(parsed.diagnostics.is_empty());
+    assert_eq!(parsed.tree.kind, SyntaxKind::Assignment("x".to_string()));
+    assert_eq!(parsed.tree.children[0].kind, SyntaxKind::Operator("*"));
+}
+
+#[test]
+fn float_hazards_are_warned_about() {
+    let mut calculator = Calculator::new();
+
+    let mut warnings = |input: &str| calculator.execute(input).unwrap().warnings().to_vec();
+
+    assert_eq!(
+        warnings("(-1)^0.5"),
+        [Warning::DomainError("(-1)^0.5".to_string())]
+    );
+    assert_eq!(warnings("0^-1"), [Warning::Infinity("0^-1".to_string())]);
+    assert_eq!(warnings("ln(0)"), [Warning::Infinity("ln(0)".to_string())]);
+    assert_eq!(
+        warnings("10^400"),
+        [Warning::Overflow("10^400".to_string())]
+    );
+    //Literals can be too large too, the infinity they become doesnt warn again
+    assert_eq!(warnings("1e400"), [Warning::Overflow("1e400".to_string())]);
+    assert_eq!(
+        warnings("2 * 1e400"),
+        [Warning::Overflow("1e400".to_string())]
+    );
+    assert_eq!(
+        warnings("0.1 + 0.2 - 0.3"),
+        [Warning::PrecisionLoss {
+            expression: "0.1 + 0.2 - 0.3".to_string(),
+            lost_digits: 15
+        }]
+    );
+
+    //Values which already are NaN or infinite dont warn again, and exact cancellations are fine
+    assert!(warnings("(-1)^0.5 + 1").len() == 1);
+    assert!(warnings("inf + 1").is_empty());
+    assert!(warnings("0.5 - 0.5").is_empty());
+    //Integers and short fractions are exact floats, their differences havent lost anything
+    assert!(warnings("123456789 - 123456788").is_empty());
+    assert!(warnings("100000000.5 - 100000000").is_empty());
+    assert_eq!(
+        warnings("pi - 3.14159265"),
+        [Warning::PrecisionLoss {
+            expression: "pi - 3.14159265".to_string(),
+            lost_digits: 9
+        }]
+    );
+
+    //Exact numbers have no rounding errors to lose digits to
+    calculator.set_number_mode(NumberMode::Decimal);
+
+    assert!(calculator
+        .execute("sqrt(2) - 1.4142135623")
+        .unwrap()
+        .warnings()
+        .is_empty());
+
+    //Complex results are checked in both parts
+    calculator.set_number_mode(NumberMode::Complex);
+
+    let mut warnings = |input: &str| calculator.execute(input).unwrap().warnings().to_vec();
+
+    assert_eq!(
+        warnings("(1 + i)^5000"),
+        [Warning::Overflow("(1 + i)^5000".to_string())]
+    );
+    //A NaN part next to an infinite one comes from subtracting infinities, the result has still overflowed
+    assert_eq!(
+        warnings("(1e200 + 1e200i) * (1e200 + 1e200i)"),
+        [Warning::Overflow(
+            "(1e200 + 1e200i) * (1e200 + 1e200i)".to_string()
+        )]
+    );
+    assert_eq!(
+        warnings("exp(1000 + i)"),
+        [Warning::Overflow("exp(1000 + i)".to_string())]
+    );
+    assert!(warnings("nan * i").is_empty());
+    assert!(warnings("inf * i").is_empty());
+
+    calculator.set_number_mode(NumberMode::Float);
+
+    //Strict calculators refuse to answer instead
+    calculator.set_strict(true);
+
+    let error = calculator.calculate("2 * sqrt(-1)").unwrap_err();
+
+    assert_eq!(
+        *error.error_type(),
+        CalculatorErrorType::Warning(Warning::DomainError("sqrt(-1)".to_string()))
+    );
+    assert_eq!(error.span(), 4..12);
+
+    let error = calculator.calculate("2 * 1e400").unwrap_err();
+
+    assert_eq!(
+        *error.error_type(),
+        CalculatorErrorType::Warning(Warning::Overflow("1e400".to_string()))
+    );
+    assert_eq!(error.span(), 4..9);
+    assert_eq!(
+        calculator.calculate("0.1 + 0.2").unwrap(),
+        0.30000000000000004
+    );
+}
+
+#[test]
+fn deep_nesting_is_an_error_instead_of_a_stack_overflow() {
+    let mut calculator = Calculator::new();
+
+    let error_type = |calculator: &mut Calculator, input: &str| {
+        calculator
+            .calculate(input)
+            .unwrap_err()
+            .error_type()
+            .clone()
+    };
+
+    //Long chains of operators are calculated in a loop, they arent nested
+    let long_sum = vec!["1"; 20000].join("+");
+
+    assert_eq!(calculator.calculate(&long_sum).unwrap(), 20000.);
+    assert!(calculator.check(&long_sum).is_empty());
+    assert_eq!(
+        calculator
+            .calculate(&format!("2 * ({long_sum}) - ({long_sum})"))
+            .unwrap(),
+        20000.
+    );
+
+    let minuses = format!("{}1", "-".repeat(20000));
+    let brackets = format!("{}1{}", "(".repeat(20000), ")".repeat(20000));
+    let nested_sums = format!("{}1{}", "1 + (".repeat(20000), ")".repeat(20000));
+
+    for input in [&minuses, &brackets, &nested_sums] {
+        assert_eq!(
+            error_type(&mut calculator, input),
+            CalculatorErrorType::NestingLimit(128)
+        );
+        assert!(!calculator.check(input).is_empty());
+    }
+
+    //Right below the limit everything still works, even with functions whose bodies are nested deeply
+    let nested = format!("{}x{}", "(1 + ".repeat(60), ")".repeat(60));
+
+    calculator.execute(&format!("f(x) = {nested}")).unwrap();
+    calculator
+        .execute(&format!("g(x) = {nested} + f(x)"))
+        .unwrap();
+
+    let call = format!("{}g(1){}", "(1 + ".repeat(60), ")".repeat(60));
+
+    assert_eq!(calculator.calculate(&call).unwrap(), 182.);
+    assert_eq!(
+        calculator.calculate(&vec!["1"; 100].join("+")).unwrap(),
+        100.
+    );
+
+    //Deep bodies stop the recursion before the call depth is reached
+    calculator
+        .execute(&format!("h(x) = {nested} + h(x)"))
+        .unwrap();
+
+    assert_eq!(
+        error_type(&mut calculator, "h(1)"),
+        CalculatorErrorType::NestingLimit(2048)
+    );
+}